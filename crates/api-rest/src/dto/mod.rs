@@ -1,5 +1,5 @@
 pub mod requests;
 pub mod responses;
 
-pub use requests::{RegisterUserRequest, RenameUserRequest};
-pub use responses::{UserResponse, SuccessResponse, ErrorResponse};
+pub use requests::{RegisterUserRequest, RenameUserRequest, CommandParams, UserQueryParams, EventHistoryParams, VerifyParams, ListUsersParams, ConsistencyParams, SearchParams, StatsParams, AuditParams, UndoLastChangeRequest, MergeUsersRequest};
+pub use responses::{UserResponse, UserStatusResponse, UserPageResponse, SearchHitResponse, SearchResultsResponse, SearchModeResponse, StatsBucketResponse, RenamedUserResponse, UserStatsResponse, BucketSizeResponse, AuditEntryResponse, AuditPageResponse, SuccessResponse, ErrorResponse, EventResponse, DryRunResponse, UndoResponse, EventMetadataResponse, StoredEventResponse, EventHistoryResponse, RebuildProgressResponse, RebuildStatusResponse, MismatchResponse, VerificationReportResponse, ProjectionStatusResponse, ProjectionInfoResponse, DeadLetterResponse};
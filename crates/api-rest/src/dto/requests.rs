@@ -1,5 +1,5 @@
 use serde::Deserialize;
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
 /// RegisterUserRequest - Request payload for creating a new user
 #[derive(Debug, Deserialize, ToSchema)]
@@ -20,3 +20,130 @@ pub struct RenameUserRequest {
     /// New user name (must be 1-255 characters)
     pub new_name: String,
 }
+
+/// CommandParams - Query options accepted by command endpoints
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CommandParams {
+    /// Validate and report the resulting events without changing state
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// UserQueryParams - Point-in-time options for fetching a single user
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UserQueryParams {
+    /// Return the user as it was at this instant (Unix timestamp in milliseconds)
+    pub as_of: Option<i64>,
+    /// Return the user as it was at this stream version (0 = registration)
+    pub version: Option<i32>,
+}
+
+/// EventHistoryParams - Paging options for a user's event stream
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventHistoryParams {
+    /// First stream version to return (default 0)
+    pub from_version: Option<i32>,
+    /// Maximum number of events to return (default 50, max 500)
+    pub limit: Option<usize>,
+}
+
+/// VerifyParams - Options for the projection consistency verifier
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct VerifyParams {
+    /// Rewrite mismatched entries from their event streams
+    #[serde(default)]
+    pub repair: bool,
+}
+
+/// ListUsersParams - Paging, sorting and filters for listing users
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListUsersParams {
+    /// Maximum number of users to return (default 50, max 500)
+    pub limit: Option<usize>,
+    /// `next_cursor` from the previous page
+    pub cursor: Option<String>,
+    /// Sort key: id (default), name or created_at; ties are broken by id
+    pub sort: Option<String>,
+    /// Sort direction: asc (default) or desc
+    pub order: Option<String>,
+    /// Only users whose name starts with this
+    pub name_prefix: Option<String>,
+    /// Only users created at or after this instant (Unix timestamp in milliseconds)
+    pub created_from: Option<i64>,
+    /// Only users created before this instant (Unix timestamp in milliseconds)
+    pub created_to: Option<i64>,
+}
+
+/// ConsistencyParams - Read-your-writes option for query endpoints
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ConsistencyParams {
+    /// Wait until the read model has applied the event at this global
+    /// position (the `position` returned by a command) before answering
+    pub min_position: Option<u64>,
+}
+
+/// SearchParams - Query for the user search endpoint
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchParams {
+    /// Text to search for (case-insensitive)
+    pub q: String,
+    /// Matching mode: prefix (default), substring or fuzzy
+    pub mode: Option<String>,
+    /// Maximum number of results (default 20, max 100)
+    pub limit: Option<usize>,
+}
+
+/// StatsParams - Options for the user statistics endpoint
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StatsParams {
+    /// Bucket size for the counters: hour, day (default) or week
+    pub bucket: Option<String>,
+    /// How many of the most renamed users to include (default 10, max 100)
+    pub top: Option<usize>,
+}
+
+/// AuditParams - Filters for the audit log; all are optional and combine
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditParams {
+    /// Only changes made by this actor (the X-Actor header of the command)
+    pub actor: Option<String>,
+    /// Only changes to this user
+    pub user_id: Option<u32>,
+    /// Only changes at or after this time (Unix timestamp in milliseconds)
+    pub from: Option<i64>,
+    /// Only changes before this time (Unix timestamp in milliseconds)
+    pub to: Option<i64>,
+    /// Only entries after this position; pass next_after from the previous page
+    pub after: Option<u64>,
+    /// Maximum number of entries (default 100, max 1000)
+    pub limit: Option<usize>,
+}
+
+/// UndoLastChangeRequest - Audit details for reverting a user's last change
+/// (who reverts it comes from the X-Actor header)
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[schema(example = json!({"reason": "Accidental rename reported in ticket 4711"}))]
+pub struct UndoLastChangeRequest {
+    /// Why the change is being reverted
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// MergeUsersRequest - Request payload for merging a duplicate account
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(example = json!({"source_id": 2, "target_id": 1}))]
+pub struct MergeUsersRequest {
+    /// Duplicate account to merge away (its ID will redirect to the target)
+    pub source_id: u32,
+    /// Account that survives the merge
+    pub target_id: u32,
+}
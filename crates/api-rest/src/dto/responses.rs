@@ -1,5 +1,10 @@
+use std::collections::BTreeMap;
 use serde::Serialize;
-use persistence::projections::UserReadModel;
+use serde_json::json;
+use domain::events::{EventEnvelope, UserEvent};
+use application::{ProjectionInfo, ProjectionStatus};
+use persistence::event_store::DeadLetterQueueEntry;
+use persistence::projections::{AuditEntry, AuditPage, BucketCounts, Mismatch, SearchHit, SearchMode, RebuildProgress, RebuildStatus, RenamedUser, StatsBucket, UserPage, UserReadModel, UserStats, UserStatus, VerificationReport};
 use utoipa::ToSchema;
 
 /// UserResponse - API response for a user
@@ -11,6 +16,35 @@ pub struct UserResponse {
     pub name: String,
     /// Timestamp when user was created (Unix timestamp in milliseconds)
     pub created_at: i64,
+    /// Version of the user's latest event (0 = registration); send it back
+    /// as the expected version for optimistic concurrency
+    pub version: i32,
+    /// Timestamp of the user's latest event (Unix timestamp in milliseconds)
+    pub updated_at: i64,
+    /// How many times the user has been renamed
+    pub rename_count: u32,
+    /// Lifecycle state. Live reads only ever return `active` because merged
+    /// users leave the projection and redirect to the survivor; `merged` is
+    /// only seen when replaying with `as_of` or `version`
+    pub status: UserStatusResponse,
+}
+
+/// UserStatusResponse - Lifecycle state of a user account
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UserStatusResponse {
+    Active,
+    /// Merged into another account (only seen in historical reads)
+    Merged,
+}
+
+impl From<UserStatus> for UserStatusResponse {
+    fn from(status: UserStatus) -> Self {
+        match status {
+            UserStatus::Active => UserStatusResponse::Active,
+            UserStatus::Merged => UserStatusResponse::Merged,
+        }
+    }
 }
 
 impl From<UserReadModel> for UserResponse {
@@ -19,6 +53,226 @@ impl From<UserReadModel> for UserResponse {
             id: model.id,
             name: model.name,
             created_at: model.created_at,
+            version: model.version,
+            updated_at: model.updated_at,
+            rename_count: model.rename_count,
+            status: model.status.into(),
+        }
+    }
+}
+
+/// UserPageResponse - One page of a user listing
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserPageResponse {
+    /// Users on this page, in the requested order
+    pub users: Vec<UserResponse>,
+    /// Pass as `cursor` to fetch the next page; null on the last page
+    pub next_cursor: Option<String>,
+}
+
+impl From<UserPage> for UserPageResponse {
+    fn from(page: UserPage) -> Self {
+        UserPageResponse {
+            users: page.users.into_iter().map(UserResponse::from).collect(),
+            next_cursor: page.next_cursor.map(|cursor| cursor.encode()),
+        }
+    }
+}
+
+/// SearchHitResponse - One ranked search result
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchHitResponse {
+    /// User's unique identifier
+    pub id: u32,
+    /// User's current name
+    pub name: String,
+    /// Lower is better: edit distance in fuzzy mode, otherwise how many
+    /// characters the name has beyond the query
+    pub score: usize,
+}
+
+impl From<SearchHit> for SearchHitResponse {
+    fn from(hit: SearchHit) -> Self {
+        SearchHitResponse {
+            id: hit.user_id,
+            name: hit.name,
+            score: hit.score,
+        }
+    }
+}
+
+/// SearchModeResponse - How a search query was matched
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchModeResponse {
+    Prefix,
+    Substring,
+    Fuzzy,
+}
+
+impl From<SearchMode> for SearchModeResponse {
+    fn from(mode: SearchMode) -> Self {
+        match mode {
+            SearchMode::Prefix => SearchModeResponse::Prefix,
+            SearchMode::Substring => SearchModeResponse::Substring,
+            SearchMode::Fuzzy => SearchModeResponse::Fuzzy,
+        }
+    }
+}
+
+/// SearchResultsResponse - Ranked results of a user search
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchResultsResponse {
+    /// The query as received
+    pub query: String,
+    /// Matching mode used
+    pub mode: SearchModeResponse,
+    /// Matches, best first
+    pub results: Vec<SearchHitResponse>,
+}
+
+/// StatsBucketResponse - User activity within one time bucket
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StatsBucketResponse {
+    /// Start of the bucket (Unix timestamp in milliseconds, UTC-aligned)
+    pub start: i64,
+    pub signups: u64,
+    pub renames: u64,
+    /// Accounts merged away into another
+    pub merges: u64,
+}
+
+impl From<BucketCounts> for StatsBucketResponse {
+    fn from(counts: BucketCounts) -> Self {
+        StatsBucketResponse {
+            start: counts.start,
+            signups: counts.signups,
+            renames: counts.renames,
+            merges: counts.merges,
+        }
+    }
+}
+
+/// RenamedUserResponse - A user ranked by rename count
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RenamedUserResponse {
+    /// User's unique identifier
+    pub id: u32,
+    /// User's current name
+    pub name: String,
+    pub rename_count: u32,
+}
+
+impl From<RenamedUser> for RenamedUserResponse {
+    fn from(user: RenamedUser) -> Self {
+        RenamedUserResponse {
+            id: user.user_id,
+            name: user.name,
+            rename_count: user.rename_count,
+        }
+    }
+}
+
+/// BucketSizeResponse - Width of the time buckets stats are reported in
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BucketSizeResponse {
+    Hour,
+    Day,
+    Week,
+}
+
+impl From<StatsBucket> for BucketSizeResponse {
+    fn from(bucket: StatsBucket) -> Self {
+        match bucket {
+            StatsBucket::Hour => BucketSizeResponse::Hour,
+            StatsBucket::Day => BucketSizeResponse::Day,
+            StatsBucket::Week => BucketSizeResponse::Week,
+        }
+    }
+}
+
+/// UserStatsResponse - User analytics over time
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({"bucket": "day", "active": 41, "deactivated": 2, "buckets": [{"start": 1700006400000_i64, "signups": 12, "renames": 3, "merges": 1}], "most_renamed": [{"id": 7, "name": "Alice Smith", "rename_count": 4}]}))]
+pub struct UserStatsResponse {
+    /// Bucket size used
+    pub bucket: BucketSizeResponse,
+    /// Users with a live account
+    pub active: u64,
+    /// Users deactivated by being merged into another account
+    pub deactivated: u64,
+    /// Counters per bucket, oldest first; empty buckets are omitted
+    pub buckets: Vec<StatsBucketResponse>,
+    /// Most renamed active users, most renamed first
+    pub most_renamed: Vec<RenamedUserResponse>,
+}
+
+impl From<UserStats> for UserStatsResponse {
+    fn from(stats: UserStats) -> Self {
+        UserStatsResponse {
+            bucket: stats.bucket.into(),
+            active: stats.active,
+            deactivated: stats.deactivated,
+            buckets: stats.buckets.into_iter().map(StatsBucketResponse::from).collect(),
+            most_renamed: stats.most_renamed.into_iter().map(RenamedUserResponse::from).collect(),
+        }
+    }
+}
+
+/// AuditEntryResponse - Who changed which user, when, and how
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({"position": 12, "timestamp": 1700000000000_i64, "user_id": 1, "event_type": "UserRenamed", "summary": "UserRenamed(id=1, new_name=Alice Smith, timestamp=1700000000000)", "actor": "support:jdoe", "correlation_id": "corr-1700000000000-1", "causation_id": null, "annotations": {}}))]
+pub struct AuditEntryResponse {
+    /// Position of the event in the store-wide log
+    pub position: u64,
+    /// When the change was stored (Unix timestamp in milliseconds)
+    pub timestamp: i64,
+    /// The user that was changed
+    pub user_id: u32,
+    /// Event type name (e.g. UserRegistered, UserRenamed)
+    pub event_type: String,
+    /// Human-readable description of the change
+    pub summary: String,
+    /// Who issued the command (null if not identified)
+    pub actor: Option<String>,
+    /// Identifies the command (request) that made the change
+    pub correlation_id: String,
+    /// ID of the event that caused this one, if any
+    pub causation_id: Option<String>,
+    /// Free-form annotations (e.g. reason)
+    pub annotations: BTreeMap<String, String>,
+}
+
+impl From<AuditEntry> for AuditEntryResponse {
+    fn from(entry: AuditEntry) -> Self {
+        AuditEntryResponse {
+            position: entry.position,
+            timestamp: entry.timestamp,
+            user_id: entry.user_id,
+            event_type: entry.event_type,
+            summary: entry.summary,
+            actor: entry.actor,
+            correlation_id: entry.correlation_id,
+            causation_id: entry.causation_id,
+            annotations: entry.annotations,
+        }
+    }
+}
+
+/// AuditPageResponse - One page of the audit log, oldest first
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuditPageResponse {
+    pub entries: Vec<AuditEntryResponse>,
+    /// Pass as `after` to fetch the next page; null on the last page
+    pub next_after: Option<u64>,
+}
+
+impl From<AuditPage> for AuditPageResponse {
+    fn from(page: AuditPage) -> Self {
+        AuditPageResponse {
+            entries: page.entries.into_iter().map(AuditEntryResponse::from).collect(),
+            next_after: page.next_after,
         }
     }
 }
@@ -28,6 +282,11 @@ impl From<UserReadModel> for UserResponse {
 pub struct SuccessResponse {
     /// Success message describing the operation result
     pub message: String,
+    /// Consistency token: global position of the write; pass it as
+    /// `min_position` on a query to read your own write
+    pub position: Option<u64>,
+    /// Stream version of the affected user after the write
+    pub version: Option<i32>,
 }
 
 /// ErrorResponse - Standard error response
@@ -36,3 +295,346 @@ pub struct ErrorResponse {
     /// Error message describing what went wrong
     pub error: String,
 }
+
+/// EventResponse - API representation of a domain event
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({"event_type": "UserRenamed", "aggregate_id": 1, "timestamp": 1700000000000i64, "data": {"new_name": "Bob"}}))]
+pub struct EventResponse {
+    /// Event type name (e.g. UserRegistered, UserRenamed)
+    pub event_type: String,
+    /// ID of the user the event belongs to
+    pub aggregate_id: u32,
+    /// When the event occurred (Unix timestamp in milliseconds)
+    pub timestamp: i64,
+    /// Event-specific fields
+    #[schema(value_type = Object)]
+    pub data: serde_json::Value,
+}
+
+impl From<UserEvent> for EventResponse {
+    fn from(event: UserEvent) -> Self {
+        let data = match &event {
+            UserEvent::Registered { name, .. } => json!({ "name": name }),
+            UserEvent::Renamed { new_name, .. } => json!({ "new_name": new_name }),
+            UserEvent::MergedInto { target_id, .. } => json!({ "target_id": target_id }),
+            UserEvent::Absorbed { source_id, .. } => json!({ "source_id": source_id }),
+        };
+        EventResponse {
+            event_type: event.event_type().to_string(),
+            aggregate_id: event.aggregate_id(),
+            timestamp: event.timestamp(),
+            data,
+        }
+    }
+}
+
+/// DryRunResponse - Events a command would produce, without applying them
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DryRunResponse {
+    /// Always true; nothing was saved or published
+    pub dry_run: bool,
+    /// Events the command would append, in order
+    pub events: Vec<EventResponse>,
+}
+
+impl DryRunResponse {
+    pub fn new(events: Vec<UserEvent>) -> Self {
+        DryRunResponse {
+            dry_run: true,
+            events: events.into_iter().map(EventResponse::from).collect(),
+        }
+    }
+}
+
+/// UndoResponse - Result of reverting a user's last change
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UndoResponse {
+    /// ID of the user whose change was reverted
+    pub user_id: u32,
+    /// ID of the event that was reverted
+    pub reverted_event_id: String,
+    /// ID of the compensating event that was appended
+    pub compensating_event_id: String,
+    /// The compensating event
+    pub event: EventResponse,
+    /// Correlation ID of the undo command
+    pub correlation_id: String,
+    /// Why the change was reverted, if given
+    pub reason: Option<String>,
+    /// Who reverted the change (the X-Actor header), if identified
+    pub actor: Option<String>,
+    /// Consistency token: global position of the compensating event
+    pub position: u64,
+    /// Stream version of the user after the undo
+    pub version: i32,
+}
+
+impl From<EventEnvelope> for UndoResponse {
+    fn from(envelope: EventEnvelope) -> Self {
+        UndoResponse {
+            user_id: envelope.aggregate_id,
+            reverted_event_id: envelope.causation_id.clone().unwrap_or_default(),
+            compensating_event_id: envelope.event_id(),
+            correlation_id: envelope.correlation_id.clone(),
+            reason: envelope.annotations.get("reason").cloned(),
+            actor: envelope.actor.clone(),
+            position: envelope.global_position,
+            version: envelope.event_version,
+            event: EventResponse::from(envelope.event),
+        }
+    }
+}
+
+/// EventMetadataResponse - Command metadata recorded with a stored event
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EventMetadataResponse {
+    /// Correlation ID of the command that produced the event
+    pub correlation_id: String,
+    /// ID of the event that caused this one, if any
+    pub causation_id: Option<String>,
+    /// Who issued the command (the X-Actor header), if identified
+    pub actor: Option<String>,
+    /// Free-form annotations (e.g. reason)
+    pub annotations: BTreeMap<String, String>,
+}
+
+/// StoredEventResponse - An event as recorded in a user's stream
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StoredEventResponse {
+    /// Position of the event in the stream (0 = registration)
+    pub version: i32,
+    /// Position of the event in the store-wide log
+    pub global_position: u64,
+    /// Stable event ID (e.g. User-1-0)
+    pub event_id: String,
+    /// Event type name (e.g. UserRegistered, UserRenamed)
+    pub event_type: String,
+    /// When the event was stored (Unix timestamp in milliseconds)
+    pub timestamp: i64,
+    /// Event-specific fields
+    #[schema(value_type = Object)]
+    pub data: serde_json::Value,
+    /// Command metadata recorded with the event
+    pub metadata: EventMetadataResponse,
+}
+
+impl From<EventEnvelope> for StoredEventResponse {
+    fn from(envelope: EventEnvelope) -> Self {
+        let event_id = envelope.event_id();
+        let event = EventResponse::from(envelope.event);
+        StoredEventResponse {
+            version: envelope.event_version,
+            global_position: envelope.global_position,
+            event_id,
+            event_type: event.event_type,
+            timestamp: envelope.timestamp,
+            data: event.data,
+            metadata: EventMetadataResponse {
+                correlation_id: envelope.correlation_id,
+                causation_id: envelope.causation_id,
+                actor: envelope.actor,
+                annotations: envelope.annotations,
+            },
+        }
+    }
+}
+
+/// EventHistoryResponse - One page of a user's event stream
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EventHistoryResponse {
+    /// ID of the user the stream belongs to
+    pub user_id: u32,
+    /// Total number of events in the stream
+    pub total: usize,
+    /// Events on this page, ordered by version
+    pub events: Vec<StoredEventResponse>,
+    /// `from_version` for the next page, or null on the last page
+    pub next_from_version: Option<i32>,
+}
+
+/// RebuildStatusResponse - Where the latest projection rebuild stands
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RebuildStatusResponse {
+    Idle,
+    Building,
+    Completed,
+    /// Ended without swapping in the new version
+    Failed,
+}
+
+impl From<RebuildStatus> for RebuildStatusResponse {
+    fn from(status: RebuildStatus) -> Self {
+        match status {
+            RebuildStatus::Idle => RebuildStatusResponse::Idle,
+            RebuildStatus::Building => RebuildStatusResponse::Building,
+            RebuildStatus::Completed => RebuildStatusResponse::Completed,
+            RebuildStatus::Failed => RebuildStatusResponse::Failed,
+        }
+    }
+}
+
+/// RebuildProgressResponse - Progress of a blue/green projection rebuild
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({"status": "building", "serving_version": 1, "target_version": 2, "events_applied": 4000, "events_total": 10000, "percent_complete": 40.0}))]
+pub struct RebuildProgressResponse {
+    pub status: RebuildStatusResponse,
+    /// Projection version currently answering queries
+    pub serving_version: u32,
+    /// Version being built (or last built), if any
+    pub target_version: Option<u32>,
+    /// Events replayed into the target version so far
+    pub events_applied: usize,
+    /// Size of the event log when the rebuild started
+    pub events_total: u64,
+    /// events_applied as a percentage of events_total (capped at 100)
+    pub percent_complete: f64,
+}
+
+impl From<RebuildProgress> for RebuildProgressResponse {
+    fn from(progress: RebuildProgress) -> Self {
+        let percent_complete = match (progress.status, progress.events_total) {
+            (RebuildStatus::Completed, _) | (_, 0) => 100.0,
+            (_, total) => (progress.events_applied as f64 / total as f64 * 100.0).min(100.0),
+        };
+        RebuildProgressResponse {
+            status: progress.status.into(),
+            serving_version: progress.serving_version,
+            target_version: progress.target_version,
+            events_applied: progress.events_applied,
+            events_total: progress.events_total,
+            percent_complete,
+        }
+    }
+}
+
+/// MismatchResponse - One disagreement between the projection and the event log
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MismatchResponse {
+    /// ID of the affected user
+    pub user_id: u32,
+    /// Human-readable description of the mismatch
+    pub description: String,
+}
+
+impl From<Mismatch> for MismatchResponse {
+    fn from(mismatch: Mismatch) -> Self {
+        MismatchResponse {
+            user_id: mismatch.user_id(),
+            description: mismatch.to_string(),
+        }
+    }
+}
+
+/// VerificationReportResponse - Result of verifying a projection against the event log
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VerificationReportResponse {
+    /// Number of user streams replayed
+    pub streams_checked: usize,
+    /// True when no mismatches were found
+    pub consistent: bool,
+    /// Every mismatch found
+    pub mismatches: Vec<MismatchResponse>,
+    /// Users whose projection entry was rewritten (empty unless repair=true)
+    pub repaired: Vec<u32>,
+}
+
+impl From<VerificationReport> for VerificationReportResponse {
+    fn from(report: VerificationReport) -> Self {
+        VerificationReportResponse {
+            streams_checked: report.streams_checked,
+            consistent: report.is_consistent(),
+            mismatches: report.mismatches.into_iter().map(MismatchResponse::from).collect(),
+            repaired: report.repaired,
+        }
+    }
+}
+
+/// ProjectionStatusResponse - Whether a projection is receiving events
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectionStatusResponse {
+    Running,
+    Stopped,
+    Rebuilding,
+    Failed,
+}
+
+impl From<ProjectionStatus> for ProjectionStatusResponse {
+    fn from(status: ProjectionStatus) -> Self {
+        match status {
+            ProjectionStatus::Running => ProjectionStatusResponse::Running,
+            ProjectionStatus::Stopped => ProjectionStatusResponse::Stopped,
+            ProjectionStatus::Rebuilding => ProjectionStatusResponse::Rebuilding,
+            ProjectionStatus::Failed => ProjectionStatusResponse::Failed,
+        }
+    }
+}
+
+/// ProjectionInfoResponse - State of one registered projection
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({"name": "user_search", "version": 1, "checkpoint": 41, "status": "running", "lag": 0, "last_error": null}))]
+pub struct ProjectionInfoResponse {
+    /// Name the projection is registered and controlled under
+    pub name: String,
+    /// Version of the data the projection is serving
+    pub version: u32,
+    /// Global position of the last applied event, if any
+    pub checkpoint: Option<u64>,
+    pub status: ProjectionStatusResponse,
+    /// Stored events the projection has not applied yet
+    pub lag: u64,
+    /// Why the projection failed, while it is failed
+    pub last_error: Option<String>,
+}
+
+impl From<ProjectionInfo> for ProjectionInfoResponse {
+    fn from(info: ProjectionInfo) -> Self {
+        ProjectionInfoResponse {
+            name: info.name,
+            version: info.version,
+            checkpoint: info.checkpoint,
+            status: info.status.into(),
+            lag: info.lag,
+            last_error: info.last_error,
+        }
+    }
+}
+
+/// DeadLetterResponse - A stored event a handler failed on after retries
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DeadLetterResponse {
+    /// ID to replay or discard the entry with
+    pub id: u64,
+    /// Handler that failed, if known
+    pub handler_name: Option<String>,
+    /// Part of the handler that failed (e.g. a projection name), if it has parts
+    pub target: Option<String>,
+    /// Stable ID of the stored event (e.g. User-1-0), if it was published stored
+    pub event_id: Option<String>,
+    /// Position of the event in the store-wide log, if known
+    pub global_position: Option<u64>,
+    pub event: EventResponse,
+    /// Error from the most recent failure
+    pub error_message: String,
+    /// Failed deliveries and replays so far
+    pub failure_count: usize,
+    /// When it last failed (Unix timestamp in milliseconds)
+    pub last_failed_at: i64,
+}
+
+impl From<DeadLetterQueueEntry> for DeadLetterResponse {
+    fn from(entry: DeadLetterQueueEntry) -> Self {
+        DeadLetterResponse {
+            id: entry.id,
+            handler_name: entry.handler_name,
+            target: entry.target,
+            event_id: entry.envelope.as_ref().map(EventEnvelope::event_id),
+            global_position: entry.envelope.as_ref().map(|envelope| envelope.global_position),
+            event: EventResponse::from(entry.event),
+            error_message: entry.error_message,
+            failure_count: entry.failure_count,
+            last_failed_at: entry.last_failed_at.timestamp_millis(),
+        }
+    }
+}
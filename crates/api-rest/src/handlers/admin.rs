@@ -0,0 +1,365 @@
+use axum::{extract::{Path, Query, State}, http::StatusCode, response::IntoResponse, Json};
+use domain::errors::{AppError, DomainResult};
+use application::{ProjectionInfo, ProjectionRegistry, ReplayError};
+use persistence::projections::ConsistencyVerifier;
+
+use crate::{dto::*, AppState};
+use super::error::error_to_response;
+
+/// Start a user projection rebuild
+/// 
+/// Builds a new version of the user read model from the event log in the
+/// background. The current version keeps serving queries until the new one
+/// has caught up, then the two are swapped atomically.
+/// Returns 202 Accepted, or 409 Conflict if a rebuild is already running.
+#[utoipa::path(
+    post,
+    path = "/admin/projections/users/rebuild",
+    responses(
+        (status = 202, description = "Rebuild started", body = RebuildProgressResponse),
+        (status = 409, description = "A rebuild is already running", body = ErrorResponse),
+    ),
+    tag = "Admin"
+)]
+pub async fn rebuild_user_projection(
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    state.logger.info("POST /admin/projections/users/rebuild");
+
+    match state.projection.start_rebuild(&state.event_store) {
+        Some(_) => {
+            let progress = state.projection.rebuild_progress();
+            state.logger.info(&format!(
+                "Rebuilding user projection as version {:?} from {} events",
+                progress.target_version, progress.events_total
+            ));
+            (StatusCode::ACCEPTED, Json(RebuildProgressResponse::from(progress))).into_response()
+        }
+        None => (
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: "A user projection rebuild is already running".to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+/// Get user projection rebuild progress
+/// 
+/// Reports which version is serving queries and how far the latest
+/// rebuild has got.
+#[utoipa::path(
+    get,
+    path = "/admin/projections/users/rebuild",
+    responses(
+        (status = 200, description = "Rebuild progress", body = RebuildProgressResponse),
+    ),
+    tag = "Admin"
+)]
+pub async fn get_user_projection_rebuild(
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    state.logger.debug("GET /admin/projections/users/rebuild");
+
+    let progress = state.projection.rebuild_progress();
+    (StatusCode::OK, Json(RebuildProgressResponse::from(progress))).into_response()
+}
+
+/// Verify the user projection against the event log
+/// 
+/// Replays every user stream and diffs the result field by field against
+/// the user read model. With `?repair=true`, mismatched entries are
+/// rewritten from their streams. Returns 200 OK with the report.
+#[utoipa::path(
+    post,
+    path = "/admin/projections/users/verify",
+    params(VerifyParams),
+    responses(
+        (status = 200, description = "Verification report", body = VerificationReportResponse),
+        (status = 500, description = "The verification run failed", body = ErrorResponse),
+    ),
+    tag = "Admin"
+)]
+pub async fn verify_user_projection(
+    State(state): State<AppState>,
+    Query(params): Query<VerifyParams>,
+) -> impl IntoResponse {
+    state.logger.info(&format!("POST /admin/projections/users/verify (repair={})", params.repair));
+
+    // Replays every stream (and may rewrite entries), so it runs off the runtime threads
+    let verifier = ConsistencyVerifier::new(state.event_store.clone(), state.projection.clone());
+    let repair = params.repair;
+    let report = match tokio::task::spawn_blocking(move || {
+        if repair {
+            verifier.verify_and_repair()
+        } else {
+            verifier.verify()
+        }
+    })
+    .await
+    {
+        Ok(report) => report,
+        Err(err) => {
+            let (status, body) = error_to_response(&AppError::RepositoryError(format!(
+                "Verification task failed: {}",
+                err
+            )));
+            return (status, body).into_response();
+        }
+    };
+
+    for mismatch in &report.mismatches {
+        state.logger.warn(&format!("Projection mismatch: {}", mismatch));
+    }
+    state.logger.info(&format!(
+        "Verified {} streams: {} mismatches, {} repaired",
+        report.streams_checked,
+        report.mismatches.len(),
+        report.repaired.len()
+    ));
+
+    (StatusCode::OK, Json(VerificationReportResponse::from(report))).into_response()
+}
+
+/// List registered projections
+/// 
+/// Reports each projection's version, checkpoint, status and lag behind
+/// the event log.
+#[utoipa::path(
+    get,
+    path = "/admin/projections",
+    responses(
+        (status = 200, description = "Registered projections", body = Vec<ProjectionInfoResponse>),
+    ),
+    tag = "Admin"
+)]
+pub async fn list_projections(
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    state.logger.debug("GET /admin/projections");
+
+    let projections: Vec<ProjectionInfoResponse> = state
+        .projections
+        .list()
+        .into_iter()
+        .map(ProjectionInfoResponse::from)
+        .collect();
+    (StatusCode::OK, Json(projections)).into_response()
+}
+
+/// Start a projection
+/// 
+/// Catches the projection up from its checkpoint and resumes delivering
+/// new events to it. Also restarts a failed projection.
+#[utoipa::path(
+    post,
+    path = "/admin/projections/{name}/start",
+    params(
+        ("name" = String, Path, description = "Registered projection name"),
+    ),
+    responses(
+        (status = 200, description = "Projection running", body = ProjectionInfoResponse),
+        (status = 404, description = "No projection with that name", body = ErrorResponse),
+        (status = 422, description = "The projection refused to catch up", body = ErrorResponse),
+        (status = 500, description = "Projection failed while catching up", body = ErrorResponse),
+    ),
+    tag = "Admin"
+)]
+pub async fn start_projection(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    state.logger.info(&format!("POST /admin/projections/{}/start", name));
+    control_projection(&state, name, ProjectionRegistry::start).await
+}
+
+/// Stop a projection
+/// 
+/// Stops delivering events to the projection; it keeps answering queries
+/// with what it has and falls behind until started again.
+#[utoipa::path(
+    post,
+    path = "/admin/projections/{name}/stop",
+    params(
+        ("name" = String, Path, description = "Registered projection name"),
+    ),
+    responses(
+        (status = 200, description = "Projection stopped", body = ProjectionInfoResponse),
+        (status = 404, description = "No projection with that name", body = ErrorResponse),
+    ),
+    tag = "Admin"
+)]
+pub async fn stop_projection(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    state.logger.info(&format!("POST /admin/projections/{}/stop", name));
+    control_projection(&state, name, ProjectionRegistry::stop).await
+}
+
+/// Rebuild a projection
+/// 
+/// Discards the projection's state, replays the whole event log into it
+/// and resumes delivery. Returns once the rebuild has finished.
+#[utoipa::path(
+    post,
+    path = "/admin/projections/{name}/rebuild",
+    params(
+        ("name" = String, Path, description = "Registered projection name"),
+    ),
+    responses(
+        (status = 200, description = "Projection rebuilt", body = ProjectionInfoResponse),
+        (status = 404, description = "No projection with that name", body = ErrorResponse),
+        (status = 422, description = "The projection refused to rebuild (e.g. a rebuild is already running)", body = ErrorResponse),
+        (status = 500, description = "Projection failed while rebuilding", body = ErrorResponse),
+    ),
+    tag = "Admin"
+)]
+pub async fn rebuild_projection(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    state.logger.info(&format!("POST /admin/projections/{}/rebuild", name));
+    control_projection(&state, name, ProjectionRegistry::rebuild).await
+}
+
+/// Run a registry control on the blocking pool: starting and rebuilding
+/// replay the event log
+async fn control_projection(
+    state: &AppState,
+    name: String,
+    control: impl FnOnce(&ProjectionRegistry, &str) -> DomainResult<ProjectionInfo> + Send + 'static,
+) -> axum::response::Response {
+    if !state.projections.names().contains(&name) {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Projection '{}' not found", name),
+            }),
+        )
+            .into_response();
+    }
+
+    let projections = state.projections.clone();
+    let result = tokio::task::spawn_blocking(move || control(&projections, &name))
+        .await
+        .unwrap_or_else(|err| Err(AppError::RepositoryError(format!("Projection task failed: {}", err))));
+    match result {
+        Ok(info) => (StatusCode::OK, Json(ProjectionInfoResponse::from(info))).into_response(),
+        Err(err) => {
+            let (status, body) = error_to_response(&err);
+            (status, body).into_response()
+        }
+    }
+}
+
+/// List dead-lettered events
+/// 
+/// Stored events that a handler still failed on after its retries, with
+/// the handler's name and the last error.
+#[utoipa::path(
+    get,
+    path = "/admin/dead-letters",
+    responses(
+        (status = 200, description = "Dead letter queue, oldest first", body = Vec<DeadLetterResponse>),
+    ),
+    tag = "Admin"
+)]
+pub async fn list_dead_letters(
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    state.logger.debug("GET /admin/dead-letters");
+
+    let entries: Vec<DeadLetterResponse> = state
+        .event_store
+        .get_dead_letter_queue()
+        .into_iter()
+        .map(DeadLetterResponse::from)
+        .collect();
+    (StatusCode::OK, Json(entries)).into_response()
+}
+
+/// Replay a dead-lettered event
+/// 
+/// Redelivers the event to the handler that failed on it, and only to
+/// that handler; entries with a target (a projection) go to that target
+/// alone, and a stopped or rebuilding projection refuses them. On success
+/// the entry is removed; if the handler fails again the entry stays queued
+/// with its failure count bumped.
+#[utoipa::path(
+    post,
+    path = "/admin/dead-letters/{id}/replay",
+    params(
+        ("id" = u64, Path, description = "Dead letter queue entry ID"),
+    ),
+    responses(
+        (status = 200, description = "Event redelivered and entry removed", body = SuccessResponse),
+        (status = 404, description = "No entry with that ID", body = ErrorResponse),
+        (status = 422, description = "The entry's handler is not subscribed", body = ErrorResponse),
+        (status = 500, description = "The handler failed again", body = ErrorResponse),
+    ),
+    tag = "Admin"
+)]
+pub async fn replay_dead_letter(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> impl IntoResponse {
+    state.logger.info(&format!("POST /admin/dead-letters/{}/replay", id));
+
+    match state.event_bus.replay_dead_letter(id).await {
+        Ok(()) => (
+            StatusCode::OK,
+            Json(SuccessResponse {
+                message: format!("Dead letter {} replayed", id),
+                position: None,
+                version: None,
+            }),
+        )
+            .into_response(),
+        Err(err) => {
+            state.logger.warn(&err.to_string());
+            let status = match err {
+                ReplayError::NotFound(_) => StatusCode::NOT_FOUND,
+                ReplayError::NotReplayable(_) | ReplayError::HandlerNotSubscribed(_) => {
+                    StatusCode::UNPROCESSABLE_ENTITY
+                }
+                ReplayError::HandlerFailed(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, Json(ErrorResponse { error: err.to_string() })).into_response()
+        }
+    }
+}
+
+/// Discard a dead-lettered event
+/// 
+/// Removes the entry without redelivering it. Returns the discarded entry.
+#[utoipa::path(
+    delete,
+    path = "/admin/dead-letters/{id}",
+    params(
+        ("id" = u64, Path, description = "Dead letter queue entry ID"),
+    ),
+    responses(
+        (status = 200, description = "Entry discarded", body = DeadLetterResponse),
+        (status = 404, description = "No entry with that ID", body = ErrorResponse),
+    ),
+    tag = "Admin"
+)]
+pub async fn discard_dead_letter(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> impl IntoResponse {
+    state.logger.info(&format!("DELETE /admin/dead-letters/{}", id));
+
+    match state.event_bus.discard_dead_letter(id) {
+        Some(entry) => (StatusCode::OK, Json(DeadLetterResponse::from(entry))).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Dead letter {} not found", id),
+            }),
+        )
+            .into_response(),
+    }
+}
@@ -1,26 +1,69 @@
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum::{extract::{Path, Query, State}, http::{HeaderMap, StatusCode}, response::IntoResponse, Json};
 
 use crate::{dto::*, AppState};
-use domain::commands::{RegisterUserCommand, RenameUserCommand};
+use domain::commands::{RegisterUserCommand, RenameUserCommand, UndoLastChangeCommand, MergeUsersCommand};
+use domain::events::EventEnvelope;
 use super::error::error_to_response;
 
+/// Header clients set to make command retries safe
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Header identifying who is issuing a command, recorded for the audit log
+pub const ACTOR_HEADER: &str = "X-Actor";
+
+/// Consistency token for a command's own write to `user_id`: the global
+/// position and stream version of the last envelope it stored there
+fn written(saved: &[EventEnvelope], user_id: u32) -> (Option<u64>, Option<i32>) {
+    saved
+        .iter()
+        .rfind(|envelope| envelope.aggregate_id == user_id)
+        .map(|envelope| (envelope.global_position, envelope.event_version))
+        .unzip()
+}
+
+fn header(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn idempotency_key(headers: &HeaderMap) -> Option<String> {
+    header(headers, IDEMPOTENCY_KEY_HEADER)
+}
+
+fn actor(headers: &HeaderMap) -> Option<String> {
+    header(headers, ACTOR_HEADER)
+}
+
 /// Register a new user
 /// 
 /// Creates a new user with the provided ID and name.
-/// Returns 201 Created on success.
+/// Returns 201 Created on success. Retries carrying the same
+/// `Idempotency-Key` header return the original outcome.
+/// With `?dry_run=true`, returns 200 OK and the events that would be produced.
 #[utoipa::path(
     post,
     path = "/users",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Client-generated key; retries with the same key are not re-executed"),
+        ("X-Actor" = Option<String>, Header, description = "Who is issuing the command, recorded in the audit log"),
+        CommandParams
+    ),
     request_body = RegisterUserRequest,
     responses(
-        (status = 201, description = "User registered successfully", body = SuccessResponse),
+        (status = 201, description = "User registered successfully, with a consistency token", body = SuccessResponse),
+        (status = 200, description = "Dry run: events the command would produce", body = DryRunResponse),
         (status = 409, description = "User with this ID already exists", body = ErrorResponse),
-        (status = 422, description = "Invalid user data (ID must be > 0, name 1-255 chars)", body = ErrorResponse),
+        (status = 422, description = "Invalid user data, or idempotency key reused for a different request", body = ErrorResponse),
     ),
     tag = "Users"
 )]
 pub async fn register_user(
     State(state): State<AppState>,
+    Query(params): Query<CommandParams>,
+    headers: HeaderMap,
     Json(payload): Json<RegisterUserRequest>,
 ) -> impl IntoResponse {
     state.logger.debug(&format!(
@@ -30,7 +73,11 @@ pub async fn register_user(
 
     // Create command - validation happens in domain layer
     let command = match RegisterUserCommand::new(payload.user_id, payload.name.clone()) {
-        Ok(cmd) => cmd,
+        Ok(cmd) => RegisterUserCommand {
+            idempotency_key: idempotency_key(&headers),
+            actor: actor(&headers),
+            ..cmd
+        },
         Err(err) => {
             state.logger.error(&format!("Invalid register command: {:?}", err));
             let (status, response) = error_to_response(&err);
@@ -38,16 +85,29 @@ pub async fn register_user(
         }
     };
 
+    if params.dry_run {
+        return match state.command_handler.dry_run_register_user(command) {
+            Ok(events) => (StatusCode::OK, Json(DryRunResponse::new(events))).into_response(),
+            Err(err) => {
+                let (status, response) = error_to_response(&err);
+                (status, response).into_response()
+            }
+        };
+    }
+
     match state.command_handler.handle_register_user(command).await {
-        Ok(_) => {
+        Ok(saved) => {
             state.logger.info(&format!(
                 "User {} registered successfully",
                 payload.user_id
             ));
+            let (position, version) = written(&saved, payload.user_id);
             (
                 StatusCode::CREATED,
                 Json(SuccessResponse {
                     message: format!("User {} registered successfully", payload.user_id),
+                    position,
+                    version,
                 }),
             )
                 .into_response()
@@ -63,21 +123,30 @@ pub async fn register_user(
 /// Rename an existing user
 /// 
 /// Updates the name of an existing user.
-/// Returns 200 OK on success.
+/// Returns 200 OK on success. Retries carrying the same
+/// `Idempotency-Key` header return the original outcome.
+/// With `?dry_run=true`, returns the events that would be produced instead.
 #[utoipa::path(
     put,
     path = "/users",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Client-generated key; retries with the same key are not re-executed"),
+        ("X-Actor" = Option<String>, Header, description = "Who is issuing the command, recorded in the audit log"),
+        CommandParams
+    ),
     request_body = RenameUserRequest,
     responses(
-        (status = 200, description = "User renamed successfully", body = SuccessResponse),
+        (status = 200, description = "User renamed successfully, or dry-run events (DryRunResponse)", body = SuccessResponse),
         (status = 404, description = "User with this ID not found", body = ErrorResponse),
         (status = 409, description = "Concurrency violation (user was modified)", body = ErrorResponse),
-        (status = 422, description = "Invalid user data (ID must be > 0, name 1-255 chars)", body = ErrorResponse),
+        (status = 422, description = "Invalid user data, or idempotency key reused for a different request", body = ErrorResponse),
     ),
     tag = "Users"
 )]
 pub async fn rename_user(
     State(state): State<AppState>,
+    Query(params): Query<CommandParams>,
+    headers: HeaderMap,
     Json(payload): Json<RenameUserRequest>,
 ) -> impl IntoResponse {
     state.logger.debug(&format!(
@@ -87,7 +156,11 @@ pub async fn rename_user(
 
     // Create command - validation happens in domain layer
     let command = match RenameUserCommand::new(payload.user_id, payload.new_name.clone()) {
-        Ok(cmd) => cmd,
+        Ok(cmd) => RenameUserCommand {
+            idempotency_key: idempotency_key(&headers),
+            actor: actor(&headers),
+            ..cmd
+        },
         Err(err) => {
             state.logger.error(&format!("Invalid rename command: {:?}", err));
             let (status, response) = error_to_response(&err);
@@ -95,16 +168,29 @@ pub async fn rename_user(
         }
     };
 
+    if params.dry_run {
+        return match state.command_handler.dry_run_rename_user(command) {
+            Ok(events) => (StatusCode::OK, Json(DryRunResponse::new(events))).into_response(),
+            Err(err) => {
+                let (status, response) = error_to_response(&err);
+                (status, response).into_response()
+            }
+        };
+    }
+
     match state.command_handler.handle_rename_user(command).await {
-        Ok(_) => {
+        Ok(saved) => {
             state.logger.info(&format!(
                 "User {} renamed successfully",
                 payload.user_id
             ));
+            let (position, version) = written(&saved, payload.user_id);
             (
                 StatusCode::OK,
                 Json(SuccessResponse {
                     message: format!("User {} renamed successfully", payload.user_id),
+                    position,
+                    version,
                 }),
             )
                 .into_response()
@@ -116,3 +202,126 @@ pub async fn rename_user(
         }
     }
 }
+
+/// Undo a user's last change
+/// 
+/// Appends a compensating event that reverts the most recent change
+/// (for example a rename back to the previous name). The restored name
+/// is re-checked for uniqueness. Returns 200 OK with audit details.
+#[utoipa::path(
+    post,
+    path = "/users/{user_id}/undo",
+    params(
+        ("user_id" = u32, Path, description = "The user's unique identifier"),
+        ("X-Actor" = Option<String>, Header, description = "Who is issuing the command, recorded in the audit log"),
+    ),
+    request_body = UndoLastChangeRequest,
+    responses(
+        (status = 200, description = "Last change reverted", body = UndoResponse),
+        (status = 404, description = "User with this ID not found", body = ErrorResponse),
+        (status = 422, description = "Nothing to undo, or the previous name is now taken", body = ErrorResponse),
+    ),
+    tag = "Users"
+)]
+pub async fn undo_last_change(
+    State(state): State<AppState>,
+    Path(user_id): Path<u32>,
+    headers: HeaderMap,
+    Json(payload): Json<UndoLastChangeRequest>,
+) -> impl IntoResponse {
+    state.logger.debug(&format!("POST /users/{}/undo", user_id));
+
+    let mut command = match UndoLastChangeCommand::new(user_id) {
+        Ok(cmd) => cmd,
+        Err(err) => {
+            state.logger.error(&format!("Invalid undo command: {:?}", err));
+            let (status, response) = error_to_response(&err);
+            return (status, response).into_response();
+        }
+    };
+    if let Some(reason) = payload.reason {
+        command = command.with_reason(reason);
+    }
+    if let Some(actor) = actor(&headers) {
+        command = command.with_actor(actor);
+    }
+
+    match state.command_handler.handle_undo_last_change(command).await {
+        Ok(envelope) => {
+            state.logger.info(&format!("User {} last change reverted", user_id));
+            (StatusCode::OK, Json(UndoResponse::from(envelope))).into_response()
+        }
+        Err(err) => {
+            state.logger.error(&format!("Failed to undo change: {:?}", err));
+            let (status, response) = error_to_response(&err);
+            (status, response).into_response()
+        }
+    }
+}
+
+/// Merge duplicate user accounts
+/// 
+/// Marks the source user as merged into the target. Afterwards lookups
+/// of the source ID redirect to the target and the source's name is
+/// released. Returns 200 OK on success.
+#[utoipa::path(
+    post,
+    path = "/users/merge",
+    params(
+        ("X-Actor" = Option<String>, Header, description = "Who is issuing the command, recorded in the audit log"),
+    ),
+    request_body = MergeUsersRequest,
+    responses(
+        (status = 200, description = "Users merged successfully; version is the target's", body = SuccessResponse),
+        (status = 404, description = "Source or target user not found", body = ErrorResponse),
+        (status = 422, description = "Invalid merge (same user, or either user already merged)", body = ErrorResponse),
+    ),
+    tag = "Users"
+)]
+pub async fn merge_users(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<MergeUsersRequest>,
+) -> impl IntoResponse {
+    state.logger.debug(&format!(
+        "POST /users/merge - merge {} into {}",
+        payload.source_id, payload.target_id
+    ));
+
+    let command = match MergeUsersCommand::new(payload.source_id, payload.target_id) {
+        Ok(cmd) => MergeUsersCommand {
+            actor: actor(&headers),
+            ..cmd
+        },
+        Err(err) => {
+            state.logger.error(&format!("Invalid merge command: {:?}", err));
+            let (status, response) = error_to_response(&err);
+            return (status, response).into_response();
+        }
+    };
+
+    match state.command_handler.handle_merge_users(command).await {
+        Ok(saved) => {
+            // Both streams were written; the later position covers both
+            let (source_position, _) = written(&saved, payload.source_id);
+            let (target_position, version) = written(&saved, payload.target_id);
+            (
+                StatusCode::OK,
+                Json(SuccessResponse {
+                    message: format!(
+                        "User {} merged into user {}",
+                        payload.source_id, payload.target_id
+                    ),
+                    position: source_position.max(target_position),
+                    version,
+                }),
+            )
+                .into_response()
+        }
+        Err(err) => {
+            state.logger.error(&format!("Failed to merge users: {:?}", err));
+            let (status, response) = error_to_response(&err);
+            (status, response).into_response()
+        }
+    }
+}
@@ -1,7 +1,9 @@
 pub mod commands;
 pub mod queries;
+pub mod admin;
 mod error;
 
-pub use commands::{register_user, rename_user};
-pub use queries::{get_user, get_all_users, find_user_by_name};
+pub use commands::{register_user, rename_user, undo_last_change, merge_users};
+pub use queries::{get_user, get_user_events, get_all_users, find_user_by_name, search_users, get_user_stats, get_audit_log};
+pub use admin::{rebuild_user_projection, get_user_projection_rebuild, verify_user_projection, list_projections, start_projection, stop_projection, rebuild_projection, list_dead_letters, replay_dead_letter, discard_dead_letter};
 pub use error::error_to_response;
@@ -1,67 +1,363 @@
-use axum::{extract::{State, Path}, http::StatusCode, response::IntoResponse, Json};
+use axum::{extract::{State, Path, Query}, http::{header, StatusCode}, response::{IntoResponse, Redirect, Response}, Json};
 
 use crate::{dto::*, AppState};
-use domain::errors::AppError;
+use domain::errors::{AppError, DomainResult};
+use domain::events::UserEvent;
+use persistence::projections::{AuditQuery, SearchMode, StatsBucket, UserCursor, UserListQuery, UserReadModel};
 use super::error::error_to_response;
 
+/// Hold a query until the projection has applied `min_position`
+/// A position that was never written is a 422; a projection that does not
+/// catch up within the configured timeout is a 503 the client may retry.
+async fn await_position(state: &AppState, params: &ConsistencyParams) -> Result<(), Response> {
+    let position = match params.min_position {
+        Some(position) => position,
+        None => return Ok(()),
+    };
+
+    if position >= state.event_store.head_position() {
+        let err = AppError::Validation(format!(
+            "min_position {} has not been written (next position is {})",
+            position,
+            state.event_store.head_position()
+        ));
+        return Err(error_to_response(&err).into_response());
+    }
+
+    if state.read_model.wait_for_position(position, state.consistency_timeout).await {
+        return Ok(());
+    }
+
+    state.logger.warn(&format!(
+        "Projection did not reach position {} within {:?}",
+        position, state.consistency_timeout
+    ));
+    Err((
+        StatusCode::SERVICE_UNAVAILABLE,
+        [(header::RETRY_AFTER, "1")],
+        Json(ErrorResponse {
+            error: format!("Read model has not caught up to position {} yet", position),
+        }),
+    )
+        .into_response())
+}
+
 /// Get a user by ID
 /// 
 /// Retrieves a single user by their unique identifier.
-/// Returns 200 OK if found, 404 Not Found otherwise.
+/// Returns 200 OK if found, 404 Not Found otherwise. IDs of users that
+/// were merged away redirect (308) to the surviving user.
+/// With `as_of` or `version` the user is replayed from its event stream
+/// as it was at that point instead of being read from the projection;
+/// only such historical reads can report a `merged` status.
+/// With `min_position` the lookup waits until that write is visible.
 #[utoipa::path(
     get,
     path = "/users/{user_id}",
     params(
-        ("user_id" = u32, Path, description = "The user's unique identifier")
+        ("user_id" = u32, Path, description = "The user's unique identifier"),
+        UserQueryParams,
+        ConsistencyParams,
     ),
     responses(
         (status = 200, description = "User found", body = UserResponse),
-        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 308, description = "User was merged; Location points at the surviving user"),
+        (status = 404, description = "User not found (or not yet registered at as_of)", body = ErrorResponse),
+        (status = 422, description = "Both as_of and version given, version out of range, or min_position not yet written", body = ErrorResponse),
+        (status = 503, description = "Read model did not reach min_position in time; retry", body = ErrorResponse),
     ),
     tag = "Users"
 )]
 pub async fn get_user(
     State(state): State<AppState>,
     Path(user_id): Path<u32>,
+    Query(params): Query<UserQueryParams>,
+    Query(consistency): Query<ConsistencyParams>,
 ) -> impl IntoResponse {
     state.logger.debug(&format!("GET /users/{}", user_id));
 
-    match state.projection.get_user(user_id) {
-        Some(user) => {
+    if params.as_of.is_some() || params.version.is_some() {
+        return match historical_user(&state, user_id, &params) {
+            Ok(user) => (StatusCode::OK, Json(user)).into_response(),
+            Err(err) => {
+                state.logger.debug(&format!("Historical lookup of user {} failed: {:?}", user_id, err));
+                let (status, response) = error_to_response(&err);
+                (status, response).into_response()
+            }
+        };
+    }
+
+    if let Err(response) = await_position(&state, &consistency).await {
+        return response;
+    }
+
+    let resolved_id = match state.read_model.resolve_id(user_id).await {
+        Ok(resolved_id) => resolved_id,
+        Err(err) => {
+            state.logger.error(&format!("Failed to resolve user {}: {:?}", user_id, err));
+            let (status, response) = error_to_response(&err);
+            return (status, response).into_response();
+        }
+    };
+    if resolved_id != user_id {
+        state.logger.debug(&format!("User {} was merged into {}", user_id, resolved_id));
+        return Redirect::permanent(&format!("/users/{}", resolved_id)).into_response();
+    }
+
+    match state.read_model.get_user(user_id).await {
+        Ok(Some(user)) => {
             state.logger.debug(&format!("User {} found", user_id));
             (StatusCode::OK, Json(UserResponse::from(user))).into_response()
         }
-        None => {
+        Ok(None) => {
             state.logger.debug(&format!("User {} not found", user_id));
             let err = AppError::AggregateNotFound(user_id);
             let (status, response) = error_to_response(&err);
             (status, response).into_response()
         }
+        Err(err) => {
+            state.logger.error(&format!("Failed to read user {}: {:?}", user_id, err));
+            let (status, response) = error_to_response(&err);
+            (status, response).into_response()
+        }
+    }
+}
+
+/// Replay a user from its stream at the requested instant or version
+fn historical_user(state: &AppState, user_id: u32, params: &UserQueryParams) -> DomainResult<UserResponse> {
+    let user = match (params.as_of, params.version) {
+        (Some(as_of), None) => state.repository.get_by_id_as_of(user_id, as_of)?,
+        (None, Some(version)) => state.repository.get_by_id_at_version(user_id, version)?,
+        _ => {
+            return Err(AppError::Validation(
+                "Specify either as_of or version, not both".to_string(),
+            ))
+        }
+    };
+
+    let events: Vec<UserEvent> = state
+        .repository
+        .get_stream(user_id)?
+        .into_iter()
+        .take_while(|envelope| envelope.event_version <= user.version)
+        .map(|envelope| envelope.event)
+        .collect();
+
+    UserReadModel::from_history(&events)
+        .map(UserResponse::from)
+        .ok_or_else(|| AppError::Validation(format!("Stream of user {} does not start with a registration", user_id)))
+}
+
+/// Page size used when no limit is given
+pub const DEFAULT_EVENT_PAGE_SIZE: usize = 50;
+/// Largest page of events a single request may ask for
+pub const MAX_EVENT_PAGE_SIZE: usize = 500;
+
+/// Get a user's event history
+/// 
+/// Returns the user's stream of stored events ordered by version, with
+/// type, timestamp and command metadata. Page through long streams with
+/// `from_version` and `limit`; `next_from_version` is null on the last page.
+#[utoipa::path(
+    get,
+    path = "/users/{user_id}/events",
+    params(
+        ("user_id" = u32, Path, description = "The user's unique identifier"),
+        EventHistoryParams,
+    ),
+    responses(
+        (status = 200, description = "Page of the user's event stream", body = EventHistoryResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 422, description = "Invalid from_version or limit", body = ErrorResponse),
+    ),
+    tag = "Users"
+)]
+pub async fn get_user_events(
+    State(state): State<AppState>,
+    Path(user_id): Path<u32>,
+    Query(params): Query<EventHistoryParams>,
+) -> impl IntoResponse {
+    state.logger.debug(&format!("GET /users/{}/events", user_id));
+
+    match event_history(&state, user_id, &params) {
+        Ok(history) => {
+            state.logger.debug(&format!(
+                "Returning {} of {} events for user {}",
+                history.events.len(), history.total, user_id
+            ));
+            (StatusCode::OK, Json(history)).into_response()
+        }
+        Err(err) => {
+            let (status, response) = error_to_response(&err);
+            (status, response).into_response()
+        }
+    }
+}
+
+fn event_history(state: &AppState, user_id: u32, params: &EventHistoryParams) -> DomainResult<EventHistoryResponse> {
+    let from_version = params.from_version.unwrap_or(0);
+    let limit = params.limit.unwrap_or(DEFAULT_EVENT_PAGE_SIZE);
+
+    if from_version < 0 {
+        return Err(AppError::Validation("from_version must be >= 0".to_string()));
+    }
+    if limit == 0 || limit > MAX_EVENT_PAGE_SIZE {
+        return Err(AppError::Validation(format!(
+            "limit must be between 1 and {}",
+            MAX_EVENT_PAGE_SIZE
+        )));
     }
+
+    let stream = state.repository.get_stream(user_id)?;
+    let total = stream.len();
+    let events: Vec<StoredEventResponse> = stream
+        .into_iter()
+        .skip(from_version as usize)
+        .take(limit)
+        .map(StoredEventResponse::from)
+        .collect();
+    let next_from_version = events
+        .last()
+        .map(|event| event.version + 1)
+        .filter(|next| (*next as usize) < total);
+
+    Ok(EventHistoryResponse {
+        user_id,
+        total,
+        events,
+        next_from_version,
+    })
 }
 
-/// Fetch all users
+/// Page size used when no limit is given
+pub const DEFAULT_USER_PAGE_SIZE: usize = 50;
+/// Largest page of users a single request may ask for
+pub const MAX_USER_PAGE_SIZE: usize = 500;
+
+/// List users
 /// 
-/// Retrieves a list of all registered users.
-/// Returns 200 OK with an array of users (may be empty).
+/// Returns one page of users in a stable order, optionally filtered by
+/// name prefix and creation time. Fetch further pages by passing the
+/// returned `next_cursor` as `cursor` with the same sort and order.
 #[utoipa::path(
     get,
     path = "/users",
+    params(ListUsersParams, ConsistencyParams),
     responses(
-        (status = 200, description = "List of all users", body = Vec<UserResponse>),
+        (status = 200, description = "Page of users", body = UserPageResponse),
+        (status = 422, description = "Invalid sort, order, limit or cursor, or min_position not yet written", body = ErrorResponse),
+        (status = 503, description = "Read model did not reach min_position in time; retry", body = ErrorResponse),
     ),
     tag = "Users"
 )]
 pub async fn get_all_users(
     State(state): State<AppState>,
+    Query(params): Query<ListUsersParams>,
+    Query(consistency): Query<ConsistencyParams>,
+) -> impl IntoResponse {
+    state.logger.debug("GET /users - list users");
+
+    if let Err(response) = await_position(&state, &consistency).await {
+        return response;
+    }
+
+    let page = match list_query(&params) {
+        Ok(query) => state.read_model.list_users(&query).await,
+        Err(err) => Err(err),
+    };
+    match page {
+        Ok(page) => {
+            state.logger.debug(&format!("Returning {} users", page.users.len()));
+            (StatusCode::OK, Json(UserPageResponse::from(page))).into_response()
+        }
+        Err(err) => {
+            let (status, response) = error_to_response(&err);
+            (status, response).into_response()
+        }
+    }
+}
+
+fn list_query(params: &ListUsersParams) -> DomainResult<UserListQuery> {
+    let limit = params.limit.unwrap_or(DEFAULT_USER_PAGE_SIZE);
+    if limit == 0 || limit > MAX_USER_PAGE_SIZE {
+        return Err(AppError::Validation(format!(
+            "limit must be between 1 and {}",
+            MAX_USER_PAGE_SIZE
+        )));
+    }
+
+    let descending = match params.order.as_deref() {
+        None | Some("asc") => false,
+        Some("desc") => true,
+        Some(other) => {
+            return Err(AppError::Validation(format!(
+                "Unknown order '{}' (expected asc or desc)",
+                other
+            )))
+        }
+    };
+
+    Ok(UserListQuery {
+        sort: params.sort.as_deref().map(str::parse).transpose()?.unwrap_or_default(),
+        descending,
+        name_prefix: params.name_prefix.clone(),
+        created_from: params.created_from,
+        created_to: params.created_to,
+        after: params.cursor.as_deref().map(UserCursor::decode).transpose()?,
+        limit,
+    })
+}
+
+/// Largest number of results a search may ask for
+pub const MAX_SEARCH_RESULTS: usize = 100;
+
+/// Search users
+/// 
+/// Type-ahead search over user names, ignoring case. `mode=prefix` matches
+/// names starting with `q`, `substring` names containing it, and `fuzzy`
+/// names within one or two typos of it. Results are ranked best first.
+#[utoipa::path(
+    get,
+    path = "/users/search",
+    params(SearchParams),
+    responses(
+        (status = 200, description = "Ranked matches (may be empty)", body = SearchResultsResponse),
+        (status = 422, description = "Invalid mode or limit", body = ErrorResponse),
+    ),
+    tag = "Users"
+)]
+pub async fn search_users(
+    State(state): State<AppState>,
+    Query(params): Query<SearchParams>,
 ) -> impl IntoResponse {
-    state.logger.debug("GET /users - fetch all users");
+    state.logger.debug(&format!("GET /users/search?q={}", params.q));
 
-    let users = state.projection.get_all_users();
-    let response: Vec<UserResponse> = users.into_iter().map(UserResponse::from).collect();
-    
-    state.logger.debug(&format!("Returning {} users", response.len()));
-    (StatusCode::OK, Json(response)).into_response()
+    let limit = params.limit.unwrap_or(20);
+    let mode = match params.mode.as_deref().map(str::parse::<SearchMode>).transpose() {
+        Ok(_) if limit == 0 || limit > MAX_SEARCH_RESULTS => Err(AppError::Validation(format!(
+            "limit must be between 1 and {}",
+            MAX_SEARCH_RESULTS
+        ))),
+        Ok(mode) => Ok(mode.unwrap_or_default()),
+        Err(err) => Err(err),
+    };
+
+    match mode {
+        Ok(mode) => {
+            let results = state.search.search(&params.q, mode, limit);
+            state.logger.debug(&format!("Search returned {} results", results.len()));
+            let response = SearchResultsResponse {
+                query: params.q,
+                mode: mode.into(),
+                results: results.into_iter().map(SearchHitResponse::from).collect(),
+            };
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(err) => {
+            let (status, response) = error_to_response(&err);
+            (status, response).into_response()
+        }
+    }
 }
 
 /// Search for a user by name
@@ -72,26 +368,39 @@ pub async fn get_all_users(
     get,
     path = "/users/search/{name}",
     params(
-        ("name" = String, Path, description = "The user's name to search for")
+        ("name" = String, Path, description = "The user's name to search for"),
+        ConsistencyParams,
     ),
     responses(
         (status = 200, description = "User found", body = UserResponse),
         (status = 404, description = "User with that name not found", body = ErrorResponse),
+        (status = 422, description = "min_position not yet written", body = ErrorResponse),
+        (status = 503, description = "Read model did not reach min_position in time; retry", body = ErrorResponse),
     ),
     tag = "Users"
 )]
 pub async fn find_user_by_name(
     State(state): State<AppState>,
     Path(name): Path<String>,
+    Query(consistency): Query<ConsistencyParams>,
 ) -> impl IntoResponse {
     state.logger.debug(&format!("GET /users/search/{}", name));
 
-    match state.projection.find_by_name(&name) {
-        Some(user) => {
+    if let Err(response) = await_position(&state, &consistency).await {
+        return response;
+    }
+
+    match state.read_model.find_by_name(&name).await {
+        Ok(Some(user)) => {
             state.logger.debug(&format!("User '{}' found", name));
             (StatusCode::OK, Json(UserResponse::from(user))).into_response()
         }
-        None => {
+        Err(err) => {
+            state.logger.error(&format!("Failed to look up user '{}': {:?}", name, err));
+            let (status, response) = error_to_response(&err);
+            (status, response).into_response()
+        }
+        Ok(None) => {
             state.logger.debug(&format!("User '{}' not found", name));
             (
                 StatusCode::NOT_FOUND,
@@ -103,3 +412,217 @@ pub async fn find_user_by_name(
         }
     }
 }
+
+/// Largest number of most renamed users the stats endpoint returns
+pub const MAX_STATS_TOP: usize = 100;
+
+/// Get user statistics
+/// 
+/// Signups, renames and merges per hour, day or week, active versus
+/// deactivated (merged away) user counts, and the most renamed users.
+#[utoipa::path(
+    get,
+    path = "/stats/users",
+    params(StatsParams),
+    responses(
+        (status = 200, description = "User statistics", body = UserStatsResponse),
+        (status = 422, description = "Invalid bucket or top", body = ErrorResponse),
+    ),
+    tag = "Stats"
+)]
+pub async fn get_user_stats(
+    State(state): State<AppState>,
+    Query(params): Query<StatsParams>,
+) -> impl IntoResponse {
+    state.logger.debug("GET /stats/users");
+
+    let top = params.top.unwrap_or(10);
+    let bucket = match params.bucket.as_deref().map(str::parse::<StatsBucket>).transpose() {
+        Ok(_) if top > MAX_STATS_TOP => Err(AppError::Validation(format!(
+            "top must be at most {}",
+            MAX_STATS_TOP
+        ))),
+        Ok(bucket) => Ok(bucket.unwrap_or_default()),
+        Err(err) => Err(err),
+    };
+
+    match bucket {
+        Ok(bucket) => {
+            let stats = state.stats.stats(bucket, top);
+            (StatusCode::OK, Json(UserStatsResponse::from(stats))).into_response()
+        }
+        Err(err) => {
+            let (status, response) = error_to_response(&err);
+            (status, response).into_response()
+        }
+    }
+}
+
+/// Largest page of audit entries a query may ask for
+pub const MAX_AUDIT_ENTRIES: usize = 1_000;
+
+/// Get the audit log
+/// 
+/// Every change to every user with who made it (the command's `X-Actor`
+/// header), when, and the correlation ID of the command. Filter by actor,
+/// user and time range; results are oldest first and paged with `after`.
+#[utoipa::path(
+    get,
+    path = "/audit",
+    params(AuditParams),
+    responses(
+        (status = 200, description = "Matching audit entries", body = AuditPageResponse),
+        (status = 422, description = "Invalid limit or time range", body = ErrorResponse),
+    ),
+    tag = "Audit"
+)]
+pub async fn get_audit_log(
+    State(state): State<AppState>,
+    Query(params): Query<AuditParams>,
+) -> impl IntoResponse {
+    state.logger.debug("GET /audit");
+
+    let limit = params.limit.unwrap_or(100);
+    if limit == 0 || limit > MAX_AUDIT_ENTRIES {
+        let err = AppError::Validation(format!("limit must be between 1 and {}", MAX_AUDIT_ENTRIES));
+        let (status, response) = error_to_response(&err);
+        return (status, response).into_response();
+    }
+    if let (Some(from), Some(to)) = (params.from, params.to) {
+        if from >= to {
+            let err = AppError::Validation("from must be before to".to_string());
+            let (status, response) = error_to_response(&err);
+            return (status, response).into_response();
+        }
+    }
+
+    let page = state.audit.query(&AuditQuery {
+        actor: params.actor,
+        user_id: params.user_id,
+        from: params.from,
+        to: params.to,
+        after: params.after,
+        limit,
+    });
+    (StatusCode::OK, Json(AuditPageResponse::from(page))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use application::{EventBus, ProjectionRegistry, UserCommandHandler};
+    use infrastructure::MockLogger;
+    use persistence::projections::{AuditLogProjection, UserSearchProjection, UserStatsProjection};
+    use persistence::{EventStore, Repository, UserProjection};
+
+    /// State over a store holding user 1's registration and `renames` renames
+    fn state_with_renames(renames: usize) -> AppState {
+        let event_store = EventStore::new();
+        event_store.append(1, UserEvent::Registered { user_id: 1, name: "Alice".to_string(), timestamp: 1_000 });
+        for rename in 0..renames {
+            event_store.append(
+                1,
+                UserEvent::Renamed { user_id: 1, new_name: format!("Alice {}", rename), timestamp: 2_000 },
+            );
+        }
+
+        let projection = UserProjection::new();
+        let repository = Arc::new(Repository::new(event_store.clone(), projection.clone()));
+        let logger = Arc::new(MockLogger::new());
+        let event_bus = EventBus::new();
+        AppState {
+            command_handler: Arc::new(UserCommandHandler::new(repository.clone(), event_bus.clone(), logger.clone())),
+            event_bus,
+            projection: projection.clone(),
+            read_model: Arc::new(projection),
+            search: UserSearchProjection::new(),
+            stats: UserStatsProjection::new(),
+            audit: AuditLogProjection::new(),
+            projections: Arc::new(ProjectionRegistry::new(event_store.clone())),
+            repository,
+            event_store,
+            logger,
+            consistency_timeout: Duration::from_millis(10),
+        }
+    }
+
+    fn page(from_version: Option<i32>, limit: Option<usize>) -> EventHistoryParams {
+        EventHistoryParams { from_version, limit }
+    }
+
+    #[test]
+    fn test_event_history_pages_through_the_stream() {
+        let state = state_with_renames(4);
+
+        let first = event_history(&state, 1, &page(None, Some(2))).unwrap();
+        let second = event_history(&state, 1, &page(first.next_from_version, Some(2))).unwrap();
+        let last = event_history(&state, 1, &page(second.next_from_version, Some(2))).unwrap();
+
+        let versions = |history: &EventHistoryResponse| history.events.iter().map(|e| e.version).collect::<Vec<_>>();
+        assert_eq!(first.total, 5);
+        assert_eq!(versions(&first), vec![0, 1]);
+        assert_eq!(first.next_from_version, Some(2));
+        assert_eq!(versions(&second), vec![2, 3]);
+        assert_eq!(second.next_from_version, Some(4));
+        assert_eq!(versions(&last), vec![4]);
+        assert_eq!(last.next_from_version, None, "Last page has no next version");
+    }
+
+    #[test]
+    fn test_event_history_page_ending_at_the_stream_end_is_the_last() {
+        let state = state_with_renames(1);
+
+        let history = event_history(&state, 1, &page(Some(0), Some(2))).unwrap();
+
+        assert_eq!(history.events.len(), 2);
+        assert_eq!(history.next_from_version, None);
+    }
+
+    #[test]
+    fn test_event_history_defaults_to_the_first_page() {
+        let state = state_with_renames(2);
+
+        let history = event_history(&state, 1, &page(None, None)).unwrap();
+
+        assert_eq!(history.events.len(), 3);
+        assert_eq!(history.events[0].event_type, "UserRegistered");
+        assert_eq!(history.next_from_version, None);
+    }
+
+    #[test]
+    fn test_event_history_rejects_out_of_range_limits() {
+        let state = state_with_renames(0);
+
+        for limit in [0, MAX_EVENT_PAGE_SIZE + 1] {
+            let result = event_history(&state, 1, &page(None, Some(limit)));
+            assert!(
+                matches!(result, Err(AppError::Validation(ref msg)) if msg.contains("limit")),
+                "limit {} should be rejected",
+                limit
+            );
+        }
+        assert!(event_history(&state, 1, &page(None, Some(MAX_EVENT_PAGE_SIZE))).is_ok());
+        assert!(event_history(&state, 1, &page(Some(-1), None)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_user_events_status_codes() {
+        let state = state_with_renames(0);
+
+        let unknown = get_user_events(State(state.clone()), Path(2), Query(page(None, None)))
+            .await
+            .into_response();
+        let too_large = get_user_events(State(state.clone()), Path(1), Query(page(None, Some(501))))
+            .await
+            .into_response();
+        let found = get_user_events(State(state), Path(1), Query(page(None, None)))
+            .await
+            .into_response();
+
+        assert_eq!(unknown.status(), StatusCode::NOT_FOUND);
+        assert_eq!(too_large.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(found.status(), StatusCode::OK);
+    }
+}
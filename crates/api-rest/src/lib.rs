@@ -1,7 +1,9 @@
 use std::sync::Arc;
+use std::time::Duration;
 use infrastructure::Logger;
-use application::UserCommandHandler;
-use persistence::UserProjection;
+use application::{EventBus, ProjectionRegistry, UserCommandHandler};
+use persistence::{EventStore, Repository, UserProjection};
+use persistence::projections::{AuditLogProjection, UserQueries, UserSearchProjection, UserStatsProjection};
 
 pub mod dto;
 pub mod handlers;
@@ -10,7 +12,21 @@ pub mod openapi;
 #[derive(Clone)]
 pub struct AppState {
     pub command_handler: Arc<UserCommandHandler>,
+    /// Bus the command handler publishes on; replays dead-lettered events
+    pub event_bus: EventBus,
+    /// In-memory projection; also backs command-side checks and admin endpoints
     pub projection: UserProjection,
+    /// Store user queries are answered from (the in-memory projection or SQLite)
+    pub read_model: Arc<dyn UserQueries>,
+    pub search: UserSearchProjection,
+    pub stats: UserStatsProjection,
+    pub audit: AuditLogProjection,
+    /// Named projections with their status and start/stop/rebuild controls
+    pub projections: Arc<ProjectionRegistry>,
+    pub repository: Arc<Repository>,
+    pub event_store: EventStore,
     pub logger: Arc<dyn Logger>,
+    /// Longest a query waits for `min_position` before giving up
+    pub consistency_timeout: Duration,
 }
 
@@ -1,16 +1,18 @@
 use axum::{
-    routing::{post, put, get},
+    routing::{delete, post, put, get},
     Router,
 };
 use std::sync::Arc;
+use std::time::Duration;
 use tower_http::cors::CorsLayer;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
-use application::{EventBus, UserCommandHandler, ProjectionEventHandler};
+use application::{EventBus, ProjectionEventHandler, ProjectionRegistry, RetryPolicy, UserCommandHandler};
 use infrastructure::{ConsoleLogger, LogLevel};
 use persistence::{EventStore, Repository, UserProjection};
-use api_rest::{handlers::{register_user, rename_user, get_user, get_all_users, find_user_by_name}, AppState, openapi::ApiDoc};
+use persistence::projections::{AuditLogProjection, NameMatching, SqliteUserProjection, UserQueries, UserSearchProjection, UserStatsProjection};
+use api_rest::{handlers::{register_user, rename_user, undo_last_change, merge_users, get_user, get_user_events, get_all_users, find_user_by_name, search_users, get_user_stats, get_audit_log, rebuild_user_projection, get_user_projection_rebuild, verify_user_projection, list_projections, start_projection, stop_projection, rebuild_projection, list_dead_letters, replay_dead_letter, discard_dead_letter}, AppState, openapi::ApiDoc};
 
 #[tokio::main]
 async fn main() {
@@ -19,27 +21,95 @@ async fn main() {
     let event_store = EventStore::new();
     
     // Initialize projection and event bus
-    let projection = UserProjection::new();
-    let event_bus = EventBus::new().with_logger(logger.clone());
-    
-    // Subscribe projection to events
-    let projection_handler = Arc::new(ProjectionEventHandler::new(projection.clone()));
-    event_bus.subscribe(projection_handler);
+    let name_matching = match std::env::var("NAME_MATCHING").as_deref() {
+        Ok("case_insensitive") => NameMatching::CaseInsensitive,
+        _ => NameMatching::Exact,
+    };
+    let projection = UserProjection::new().with_name_matching(name_matching);
+    let event_bus = EventBus::new()
+        .with_logger(logger.clone())
+        .with_retry_policy(RetryPolicy::default())
+        .with_dead_letter_queue(event_store.clone());
     
+    // Commands check name uniqueness against this projection, so it stays a
+    // Critical subscriber of its own rather than one that can be stopped
+    projection.catch_up(&event_store);
+    event_bus.subscribe(Arc::new(ProjectionEventHandler::new(projection.clone())));
+
+    // Every other read model is registered here and fed by the registry
+    let projections = Arc::new(ProjectionRegistry::new(event_store.clone()).with_logger(logger.clone()));
+    let search = UserSearchProjection::new();
+    projections.register(Arc::new(search.clone())).expect("Failed to register projection");
+    let stats = UserStatsProjection::new();
+    projections.register(Arc::new(stats.clone())).expect("Failed to register projection");
+    let audit = AuditLogProjection::new();
+    projections.register(Arc::new(audit.clone())).expect("Failed to register projection");
+
+    // Queries are answered from memory unless READ_MODEL=sqlite
+    let read_model: Arc<dyn UserQueries> = match std::env::var("READ_MODEL").as_deref() {
+        Ok("sqlite") => {
+            let path = std::env::var("READ_MODEL_PATH").unwrap_or_else(|_| "users.db".to_string());
+            let sqlite = SqliteUserProjection::open(&path)
+                .and_then(|sqlite| sqlite.with_name_matching(name_matching))
+                .expect("Failed to open SQLite read model");
+            let cleared = {
+                let (sqlite, event_store) = (sqlite.clone(), event_store.clone());
+                tokio::task::spawn_blocking(move || sqlite.attach(&event_store))
+                    .await
+                    .expect("SQLite attach task panicked")
+            };
+            if cleared.expect("Failed to attach SQLite read model") {
+                (logger.as_ref() as &dyn infrastructure::Logger).warn(&format!(
+                    "SQLite read model at {} was built from another event log; rebuilding it",
+                    path
+                ));
+            }
+            projections.register(Arc::new(sqlite.clone())).expect("Failed to register projection");
+            (logger.as_ref() as &dyn infrastructure::Logger).info(&format!("Serving queries from SQLite read model at {}", path));
+            Arc::new(sqlite)
+        }
+        _ => Arc::new(projection.clone()),
+    };
+
+    // Bring every projection up to date with whatever the log already holds;
+    // replaying the log is blocking work, so it runs off the runtime threads
+    let starting = projections.clone();
+    tokio::task::spawn_blocking(move || starting.start_all())
+        .await
+        .expect("Projection start-up task panicked")
+        .expect("Failed to start projections");
+    event_bus.subscribe(projections.clone());
+
     // Create repository with both event store and projection
-    let repository = Arc::new(Repository::new(event_store, projection.clone()));
+    let repository = Arc::new(Repository::new(event_store.clone(), projection.clone()));
 
     // Create command handler
     let command_handler = Arc::new(UserCommandHandler::new(
         repository.clone(),
-        event_bus,
+        event_bus.clone(),
         logger.clone(),
     ));
 
+    // How long reads with min_position wait for the projection to catch up
+    let consistency_timeout = std::env::var("CONSISTENCY_TIMEOUT_MS")
+        .ok()
+        .and_then(|millis| millis.parse::<u64>().ok())
+        .map(Duration::from_millis)
+        .unwrap_or(Duration::from_secs(5));
+
     let state = AppState {
         command_handler,
+        event_bus,
         projection: projection.clone(),
+        read_model,
+        search,
+        stats,
+        audit,
+        projections,
+        repository,
+        event_store,
         logger: logger.clone(),
+        consistency_timeout,
     };
 
     // Build router with routes
@@ -47,8 +117,24 @@ async fn main() {
         .route("/users", post(register_user))
         .route("/users", get(get_all_users))
         .route("/users", put(rename_user))
+        .route("/users/merge", post(merge_users))
         .route("/users/:user_id", get(get_user))
+        .route("/users/:user_id/events", get(get_user_events))
+        .route("/users/:user_id/undo", post(undo_last_change))
+        .route("/users/search", get(search_users))
         .route("/users/search/:name", get(find_user_by_name))
+        .route("/stats/users", get(get_user_stats))
+        .route("/audit", get(get_audit_log))
+        .route("/admin/projections/users/rebuild", post(rebuild_user_projection))
+        .route("/admin/projections/users/rebuild", get(get_user_projection_rebuild))
+        .route("/admin/projections/users/verify", post(verify_user_projection))
+        .route("/admin/projections", get(list_projections))
+        .route("/admin/projections/:name/start", post(start_projection))
+        .route("/admin/projections/:name/stop", post(stop_projection))
+        .route("/admin/projections/:name/rebuild", post(rebuild_projection))
+        .route("/admin/dead-letters", get(list_dead_letters))
+        .route("/admin/dead-letters/:id", delete(discard_dead_letter))
+        .route("/admin/dead-letters/:id/replay", post(replay_dead_letter))
         .merge(SwaggerUi::new("/swagger-ui").url("/openapi.json", ApiDoc::openapi()))
         .layer(CorsLayer::permissive())
         .with_state(state);
@@ -1,5 +1,5 @@
 use utoipa::OpenApi;
-use crate::dto::{RegisterUserRequest, RenameUserRequest, UserResponse, SuccessResponse, ErrorResponse};
+use crate::dto::{RegisterUserRequest, RenameUserRequest, UserResponse, UserStatusResponse, UserPageResponse, SearchHitResponse, SearchResultsResponse, SearchModeResponse, StatsBucketResponse, RenamedUserResponse, UserStatsResponse, BucketSizeResponse, AuditEntryResponse, AuditPageResponse, SuccessResponse, ErrorResponse, EventResponse, DryRunResponse, UndoLastChangeRequest, UndoResponse, MergeUsersRequest, EventMetadataResponse, StoredEventResponse, EventHistoryResponse, RebuildProgressResponse, RebuildStatusResponse, MismatchResponse, VerificationReportResponse, ProjectionInfoResponse, ProjectionStatusResponse, DeadLetterResponse};
 
 /// OpenAPI documentation for the User Management API
 #[derive(OpenApi)]
@@ -7,12 +7,28 @@ use crate::dto::{RegisterUserRequest, RenameUserRequest, UserResponse, SuccessRe
     paths(
         crate::handlers::commands::register_user,
         crate::handlers::commands::rename_user,
+        crate::handlers::commands::undo_last_change,
+        crate::handlers::commands::merge_users,
         crate::handlers::queries::get_user,
+        crate::handlers::queries::get_user_events,
         crate::handlers::queries::get_all_users,
         crate::handlers::queries::find_user_by_name,
+        crate::handlers::queries::search_users,
+        crate::handlers::queries::get_user_stats,
+        crate::handlers::queries::get_audit_log,
+        crate::handlers::admin::rebuild_user_projection,
+        crate::handlers::admin::get_user_projection_rebuild,
+        crate::handlers::admin::verify_user_projection,
+        crate::handlers::admin::list_projections,
+        crate::handlers::admin::start_projection,
+        crate::handlers::admin::stop_projection,
+        crate::handlers::admin::rebuild_projection,
+        crate::handlers::admin::list_dead_letters,
+        crate::handlers::admin::replay_dead_letter,
+        crate::handlers::admin::discard_dead_letter,
     ),
     components(
-        schemas(RegisterUserRequest, RenameUserRequest, UserResponse, SuccessResponse, ErrorResponse)
+        schemas(RegisterUserRequest, RenameUserRequest, UserResponse, UserPageResponse, SearchHitResponse, SearchResultsResponse, SearchModeResponse, StatsBucketResponse, RenamedUserResponse, UserStatsResponse, BucketSizeResponse, AuditEntryResponse, AuditPageResponse, UserStatusResponse, SuccessResponse, ErrorResponse, EventResponse, DryRunResponse, UndoLastChangeRequest, UndoResponse, MergeUsersRequest, EventMetadataResponse, StoredEventResponse, EventHistoryResponse, RebuildProgressResponse, RebuildStatusResponse, MismatchResponse, VerificationReportResponse, ProjectionInfoResponse, ProjectionStatusResponse, DeadLetterResponse)
     ),
     info(
         title = "User Management API",
@@ -30,7 +46,10 @@ use crate::dto::{RegisterUserRequest, RenameUserRequest, UserResponse, SuccessRe
         (url = "http://127.0.0.1:3000", description = "Local development server")
     ),
     tags(
-        (name = "Users", description = "User management endpoints")
+        (name = "Users", description = "User management endpoints"),
+        (name = "Stats", description = "User analytics"),
+        (name = "Audit", description = "Who changed which user, and when"),
+        (name = "Admin", description = "Operational endpoints for projections and dead letters")
     )
 )]
 pub struct ApiDoc;
@@ -1,10 +1,17 @@
 // Event Bus for pub/sub
 use std::sync::{Arc, Mutex};
-use domain::events::UserEvent;
+use domain::errors::{AppError, DomainResult};
+use domain::events::{EventEnvelope, UserEvent};
 use async_trait::async_trait;
 use std::fmt;
 use infrastructure::Logger;
 use infrastructure::MetricsRegistry;
+use persistence::event_store::{DeadLetterQueueEntry, EventStore};
+use std::time::{Duration, Instant};
+use crate::retry::RetryPolicy;
+
+/// Longest a single handler attempt may run
+const HANDLER_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// HandlerError
 #[derive(Debug, Clone)]
@@ -12,6 +19,8 @@ pub struct HandlerError {
     pub handler_name: String,
     pub error_message: String,
     pub is_critical: bool,
+    /// Targets of the handler that failed, if it reported them
+    pub failed_targets: Vec<TargetFailure>,
 }
 
 impl fmt::Display for HandlerError {
@@ -22,6 +31,34 @@ impl fmt::Display for HandlerError {
 
 impl std::error::Error for HandlerError {}
 
+/// TargetFailure - One named part of a handler that failed on an event
+#[derive(Debug, Clone)]
+pub struct TargetFailure {
+    pub target: String,
+    pub error_message: String,
+}
+
+/// TargetFailures - Error for handlers that feed several named targets
+///
+/// Returned from `handle_envelope` when some targets failed, so the bus
+/// dead-letters the event once per target and replays each entry to its
+/// target alone through `EventHandler::redeliver`.
+#[derive(Debug, Clone)]
+pub struct TargetFailures(pub Vec<TargetFailure>);
+
+impl fmt::Display for TargetFailures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failures: Vec<String> = self
+            .0
+            .iter()
+            .map(|failure| format!("'{}': {}", failure.target, failure.error_message))
+            .collect();
+        write!(f, "Target(s) failed: {}", failures.join("; "))
+    }
+}
+
+impl std::error::Error for TargetFailures {}
+
 /// PublishError
 #[derive(Debug, Clone)]
 pub enum PublishError {
@@ -42,7 +79,41 @@ impl fmt::Display for PublishError {
 
 impl std::error::Error for PublishError {}
 
-/// HandlerPriority
+/// ReplayError - Why a dead-lettered event could not be redelivered
+#[derive(Debug, Clone)]
+pub enum ReplayError {
+    /// No dead letter queue entry has this ID
+    NotFound(u64),
+    /// The entry has no handler or envelope to redeliver
+    NotReplayable(u64),
+    /// No subscribed handler has the entry's handler name
+    HandlerNotSubscribed(String),
+    /// The handler failed again; the entry stays queued
+    HandlerFailed(HandlerError),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::NotFound(id) => write!(f, "Dead letter {} not found", id),
+            ReplayError::NotReplayable(id) => write!(f, "Dead letter {} has no handler or envelope to replay", id),
+            ReplayError::HandlerNotSubscribed(name) => write!(f, "Handler '{}' is not subscribed", name),
+            ReplayError::HandlerFailed(err) => write!(f, "Replay failed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// HandlerPriority - Dispatch order of a handler, and whether its failure
+/// fails the publish
+///
+/// Handlers run from Critical down to Low, in subscription order within a
+/// level. Every Critical handler runs; if any of them fails, the publish
+/// fails and no lower-priority handler sees the event. Stored events are
+/// dead-lettered for each skipped handler instead, so replaying delivers
+/// them once the failure is dealt with. Failures below Critical are
+/// reported but never stop the remaining handlers.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum HandlerPriority {
     Critical = 3,
@@ -55,14 +126,42 @@ pub enum HandlerPriority {
 #[async_trait]
 pub trait EventHandler: Send + Sync {
     async fn handle_event(&self, event: &UserEvent) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Handle a stored event; handlers that checkpoint override this to see
+    /// the global position. Defaults to `handle_event`.
+    async fn handle_envelope(&self, envelope: &EventEnvelope) -> Result<(), Box<dyn std::error::Error>> {
+        self.handle_event(&envelope.event).await
+    }
     
     fn priority(&self) -> HandlerPriority {
         HandlerPriority::Normal
     }
     
+    /// Name the handler is subscribed, measured and dead-lettered under;
+    /// unique per bus
     fn name(&self) -> &str {
         "UnnamedHandler"
     }
+
+    /// Redeliver a dead-lettered envelope to one target this handler named
+    /// in `TargetFailures`; handlers without targets keep the default
+    async fn redeliver(&self, target: &str, _envelope: &EventEnvelope) -> Result<(), Box<dyn std::error::Error>> {
+        Err(format!("Handler '{}' has no target '{}'", self.name(), target).into())
+    }
+
+    /// How failures of this handler are retried; None uses the bus default
+    fn retry_policy(&self) -> Option<RetryPolicy> {
+        None
+    }
+}
+
+/// What a publish call hands to each handler
+#[derive(Clone, Copy)]
+enum Delivery<'a> {
+    Event(&'a UserEvent),
+    Envelope(&'a EventEnvelope),
+    /// A dead-lettered envelope, for one target of the handler
+    Redelivery(&'a str, &'a EventEnvelope),
 }
 
 /// EventBus
@@ -71,6 +170,10 @@ pub struct EventBus {
     subscribers: Arc<Mutex<Vec<Arc<dyn EventHandler>>>>,
     logger: Arc<dyn Logger>,
     metrics: MetricsRegistry,
+    /// Policy for handlers that do not bring their own
+    retry_policy: RetryPolicy,
+    /// Store whose dead letter queue receives failed deliveries, if any
+    dead_letters: Option<EventStore>,
 }
 
 impl EventBus {
@@ -79,6 +182,8 @@ impl EventBus {
             subscribers: Arc::new(Mutex::new(Vec::new())),
             logger: Arc::new(infrastructure::ConsoleLogger::default()),
             metrics: MetricsRegistry::new(),
+            retry_policy: RetryPolicy::none(),
+            dead_letters: None,
         }
     }
     
@@ -87,61 +192,266 @@ impl EventBus {
         self
     }
 
+    pub fn with_metrics(mut self, metrics: MetricsRegistry) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Retry handlers without a policy of their own (default: no retries)
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Dead-letter stored events that a handler still fails on after retries
+    pub fn with_dead_letter_queue(mut self, event_store: EventStore) -> Self {
+        self.dead_letters = Some(event_store);
+        self
+    }
+
+    /// Per-handler execution, retry and timeout counters
+    pub fn metrics(&self) -> &MetricsRegistry {
+        &self.metrics
+    }
+
+    /// Add a handler after every subscriber of the same or higher priority
+    /// Names identify handlers in metrics and dead letters, so a second
+    /// handler with a name already subscribed is logged and skipped; use
+    /// `try_subscribe` to get the rejection as an error instead.
     pub fn subscribe<H: EventHandler + 'static>(&self, handler: Arc<H>) {
-        self.subscribers.lock().unwrap().push(handler as Arc<dyn EventHandler>);
+        if let Err(err) = self.try_subscribe(handler) {
+            self.logger.warn(&format!("Handler not subscribed: {}", err));
+        }
+    }
+
+    /// Like `subscribe`, but fails if a handler with the same name is
+    /// already subscribed
+    pub fn try_subscribe<H: EventHandler + 'static>(&self, handler: Arc<H>) -> DomainResult<()> {
+        let mut subscribers = self.subscribers.lock().map_err(|_| AppError::LockPoisoned)?;
+        if subscribers.iter().any(|subscriber| subscriber.name() == handler.name()) {
+            return Err(AppError::Validation(format!(
+                "A handler named '{}' is already subscribed",
+                handler.name()
+            )));
+        }
+        let priority = handler.priority();
+        let position = subscribers.partition_point(|subscriber| subscriber.priority() >= priority);
+        subscribers.insert(position, handler as Arc<dyn EventHandler>);
+        Ok(())
     }
 
     pub async fn publish(&self, event: &UserEvent) -> Result<Vec<HandlerError>, PublishError> {
         self.logger.info(&format!("Publishing event: {:?}", event));
-        
+        self.deliver(Delivery::Event(event)).await
+    }
+
+    /// Publish a stored event, giving handlers its envelope
+    pub async fn publish_envelope(&self, envelope: &EventEnvelope) -> Result<Vec<HandlerError>, PublishError> {
+        self.logger.info(&format!(
+            "Publishing event: {:?} [position={}]",
+            envelope.event, envelope.global_position
+        ));
+        self.deliver(Delivery::Envelope(envelope)).await
+    }
+
+    async fn deliver(&self, delivery: Delivery<'_>) -> Result<Vec<HandlerError>, PublishError> {
         let subscribers = {
             let subs = self.subscribers.lock()
                 .map_err(|_| PublishError::LockPoisoned)?;
             subs.iter().map(Arc::clone).collect::<Vec<_>>()
         };
         
+        // Subscribers are kept in priority order, Critical first
+        let split = subscribers.partition_point(|handler| handler.priority() == HandlerPriority::Critical);
+        let (critical, rest) = subscribers.split_at(split);
+
+        let mut critical_failure = None;
+        for handler in critical {
+            if let Err(err) = self.deliver_to(handler.as_ref(), delivery).await {
+                self.dead_letter(&err, delivery);
+                self.logger.error(&format!("Critical handler failed: {}", err));
+                critical_failure.get_or_insert(err);
+            }
+        }
+        if let Some(err) = critical_failure {
+            for handler in rest {
+                let skipped = HandlerError {
+                    handler_name: handler.name().to_string(),
+                    error_message: format!("Skipped after critical handler '{}' failed", err.handler_name),
+                    is_critical: false,
+                    failed_targets: Vec::new(),
+                };
+                self.dead_letter(&skipped, delivery);
+            }
+            if !rest.is_empty() {
+                self.logger.warn(&format!(
+                    "Skipped {} lower-priority handler(s) after a critical failure",
+                    rest.len()
+                ));
+            }
+            return Err(PublishError::CriticalHandlerFailed(err));
+        }
+
         let mut errors = Vec::new();
-        
-        for handler in subscribers {
-            match tokio::time::timeout(
-                std::time::Duration::from_secs(30),
-                handler.handle_event(event)
-            ).await {
+        for handler in rest {
+            if let Err(err) = self.deliver_to(handler.as_ref(), delivery).await {
+                self.dead_letter(&err, delivery);
+                self.logger.warn(&format!("Non-critical handler failed: {}", err));
+                errors.push(err);
+            }
+        }
+
+        Ok(errors)
+    }
+
+    /// Queue a failed envelope delivery for replay, once per failed target
+    /// if the handler reported them; bare events are not stored
+    fn dead_letter(&self, err: &HandlerError, delivery: Delivery<'_>) {
+        let (Some(store), Delivery::Envelope(envelope)) = (&self.dead_letters, delivery) else {
+            return;
+        };
+        if err.failed_targets.is_empty() {
+            let id = store.record_failed_delivery(&err.handler_name, None, envelope, err.error_message.clone());
+            self.logger.warn(&format!(
+                "Dead-lettered position {} for handler '{}' as entry {}",
+                envelope.global_position, err.handler_name, id
+            ));
+        }
+        for failure in &err.failed_targets {
+            let id = store.record_failed_delivery(
+                &err.handler_name,
+                Some(&failure.target),
+                envelope,
+                failure.error_message.clone(),
+            );
+            self.logger.warn(&format!(
+                "Dead-lettered position {} for '{}' of handler '{}' as entry {}",
+                envelope.global_position, failure.target, err.handler_name, id
+            ));
+        }
+    }
+
+    /// Redeliver a dead-lettered event to the handler (or target) that
+    /// failed on it
+    /// On success the entry is removed; on failure it stays queued with its
+    /// failure count bumped.
+    pub async fn replay_dead_letter(&self, id: u64) -> Result<(), ReplayError> {
+        let store = self.dead_letters.as_ref().ok_or(ReplayError::NotFound(id))?;
+        let entry = store.get_dlq_entry(id).ok_or(ReplayError::NotFound(id))?;
+        let (handler_name, envelope) = match (&entry.handler_name, &entry.envelope) {
+            (Some(handler_name), Some(envelope)) => (handler_name, envelope),
+            _ => return Err(ReplayError::NotReplayable(id)),
+        };
+        let handler = self
+            .subscriber(handler_name)
+            .ok_or_else(|| ReplayError::HandlerNotSubscribed(handler_name.clone()))?;
+
+        self.logger.info(&format!(
+            "Replaying dead letter {} (position {}) to handler '{}'",
+            id, envelope.global_position, handler_name
+        ));
+        let delivery = match &entry.target {
+            Some(target) => Delivery::Redelivery(target, envelope),
+            None => Delivery::Envelope(envelope),
+        };
+        match self.deliver_to(handler.as_ref(), delivery).await {
+            Ok(()) => {
+                store.remove_dlq_entry(id);
+                Ok(())
+            }
+            Err(err) => {
+                store.record_failed_delivery(
+                    handler_name,
+                    entry.target.as_deref(),
+                    envelope,
+                    err.error_message.clone(),
+                );
+                Err(ReplayError::HandlerFailed(err))
+            }
+        }
+    }
+
+    /// Drop a dead-lettered event without redelivering it
+    pub fn discard_dead_letter(&self, id: u64) -> Option<DeadLetterQueueEntry> {
+        let entry = self.dead_letters.as_ref()?.remove_dlq_entry(id)?;
+        self.logger.info(&format!("Discarded dead letter {}", id));
+        Some(entry)
+    }
+
+    /// The subscribed handler with this name
+    fn subscriber(&self, name: &str) -> Option<Arc<dyn EventHandler>> {
+        self.subscribers
+            .lock()
+            .ok()?
+            .iter()
+            .find(|handler| handler.name() == name)
+            .cloned()
+    }
+
+    /// Run one handler under its retry policy
+    /// Timeouts are not retried: the handler may still be running.
+    async fn deliver_to(&self, handler: &dyn EventHandler, delivery: Delivery<'_>) -> Result<(), HandlerError> {
+        let name = handler.name();
+        let policy = handler.retry_policy().unwrap_or_else(|| self.retry_policy.clone());
+        let mut attempt = 1;
+
+        loop {
+            let started = Instant::now();
+            let attempt_result = tokio::time::timeout(
+                HANDLER_TIMEOUT,
+                match delivery {
+                    Delivery::Event(event) => handler.handle_event(event),
+                    Delivery::Envelope(envelope) => handler.handle_envelope(envelope),
+                    Delivery::Redelivery(target, envelope) => handler.redeliver(target, envelope),
+                }
+            );
+
+            // The handler's error is not Send, so it must not outlive this match
+            let (error_message, retryable, failed_targets) = match attempt_result.await {
                 Ok(Ok(())) => {
-                    self.metrics.record_success(handler.name(), 0);
+                    let elapsed_ms = started.elapsed().as_millis() as u64;
+                    self.metrics.record_success(name, elapsed_ms);
+                    if attempt > 1 {
+                        self.metrics.record_retry_success(name);
+                    }
+                    return Ok(());
                 }
                 Ok(Err(e)) => {
-                    let err = HandlerError {
-                        handler_name: handler.name().to_string(),
-                        error_message: e.to_string(),
-                        is_critical: handler.priority() == HandlerPriority::Critical,
-                    };
-                    
-                    if err.is_critical {
-                        self.logger.error(&format!("Critical handler failed: {}", err));
-                        return Err(PublishError::CriticalHandlerFailed(err));
-                    } else {
-                        self.logger.warn(&format!("Non-critical handler failed: {}", err));
-                        errors.push(err);
-                    }
+                    let elapsed_ms = started.elapsed().as_millis() as u64;
+                    self.metrics.record_failure(name, elapsed_ms);
+                    let failed_targets = e
+                        .downcast_ref::<TargetFailures>()
+                        .map(|failures| failures.0.clone())
+                        .unwrap_or_default();
+                    (e.to_string(), policy.is_retryable(e.as_ref()), failed_targets)
                 }
                 Err(_) => {
-                    let err = HandlerError {
-                        handler_name: handler.name().to_string(),
-                        error_message: "Handler timeout".to_string(),
-                        is_critical: handler.priority() == HandlerPriority::Critical,
-                    };
-                    
-                    if err.is_critical {
-                        return Err(PublishError::CriticalHandlerFailed(err));
-                    } else {
-                        errors.push(err);
-                    }
+                    self.metrics.record_timeout(name);
+                    ("Handler timeout".to_string(), false, Vec::new())
                 }
+            };
+
+            if !retryable || attempt >= policy.max_attempts() {
+                if attempt > 1 {
+                    self.metrics.record_retry_failure(name);
+                }
+                return Err(HandlerError {
+                    handler_name: name.to_string(),
+                    error_message,
+                    is_critical: handler.priority() == HandlerPriority::Critical,
+                    failed_targets,
+                });
             }
+
+            let delay = policy.backoff(attempt);
+            self.logger.warn(&format!(
+                "Handler '{}' failed (attempt {}/{}), retrying in {}ms: {}",
+                name, attempt, policy.max_attempts(), delay.as_millis(), error_message
+            ));
+            tokio::time::sleep(delay).await;
+            self.metrics.record_retry(name);
+            attempt += 1;
         }
-        
-        Ok(errors)
     }
 }
 
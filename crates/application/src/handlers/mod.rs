@@ -1,23 +1,29 @@
 // Command handlers
 use std::sync::Arc;
-use domain::{commands::{RegisterUserCommand, RenameUserCommand}, User, errors::DomainResult, IRepository};
+use domain::{commands::{RegisterUserCommand, RenameUserCommand, UndoLastChangeCommand, MergeUsersCommand}, User, errors::DomainResult, IRepository};
+use domain::events::{EventEnvelope, EventMetadata, UserEvent};
+use domain::clock::{Clock, SystemClock};
 use infrastructure::Logger;
 use persistence::Repository;
 use crate::EventBus;
+use crate::correlation::{CorrelationIdGenerator, TimestampIdGenerator};
+use crate::idempotency::IdempotencyStore;
+use async_trait::async_trait;
 
-fn generate_correlation_id() -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let nanos = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_nanos();
-    format!("cmd_{}", nanos)
+/// Dispatch<C> - Routes a command of type C to its handler method
+/// Lets generic callers (test scenarios, batch runners) execute any command.
+#[async_trait]
+pub trait Dispatch<C>: Send + Sync {
+    async fn dispatch(&self, command: C) -> DomainResult<()>;
 }
 
 pub struct UserCommandHandler {
     repository: Arc<Repository>,
     event_bus: EventBus,
     logger: Arc<dyn Logger>,
+    clock: Arc<dyn Clock>,
+    id_generator: Arc<dyn CorrelationIdGenerator>,
+    idempotency: Arc<IdempotencyStore>,
 }
 
 impl UserCommandHandler {
@@ -30,78 +36,271 @@ impl UserCommandHandler {
             repository,
             event_bus,
             logger,
+            clock: Arc::new(SystemClock),
+            id_generator: Arc::new(TimestampIdGenerator),
+            idempotency: Arc::new(IdempotencyStore::default()),
         }
     }
 
-    pub async fn handle_register_user(&self, command: RegisterUserCommand) -> DomainResult<()> {
-        let correlation_id = generate_correlation_id();
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_id_generator(mut self, id_generator: Arc<dyn CorrelationIdGenerator>) -> Self {
+        self.id_generator = id_generator;
+        self
+    }
+
+    pub fn with_idempotency_store(mut self, idempotency: Arc<IdempotencyStore>) -> Self {
+        self.idempotency = idempotency;
+        self
+    }
+
+    /// Run a command at most once per idempotency key
+    /// Replays under a known key return the recorded outcome, success or
+    /// validation failure, without executing; a replay that arrives while
+    /// the first execution is still running waits for its outcome. Transient
+    /// failures are not recorded, so retrying after one executes again.
+    async fn run_idempotent<F>(
+        &self,
+        key: Option<&str>,
+        fingerprint: String,
+        execute: F,
+    ) -> DomainResult<Vec<EventEnvelope>>
+    where
+        F: std::future::Future<Output = DomainResult<Vec<EventEnvelope>>>,
+    {
+        let key = match key {
+            Some(key) => key,
+            None => return execute.await,
+        };
+
+        let (result, replayed) = self
+            .idempotency
+            .run(key, &fingerprint, self.clock.now_millis(), execute)
+            .await?;
+        if replayed {
+            self.logger.info(&format!(
+                "Replayed recorded outcome for idempotency key '{}'",
+                key
+            ));
+        }
+        result
+    }
+
+    /// Register a user; returns the stored envelopes
+    pub async fn handle_register_user(&self, command: RegisterUserCommand) -> DomainResult<Vec<EventEnvelope>> {
+        let key = command.idempotency_key.clone();
+        let fingerprint = format!("RegisterUser:{}:{}", command.user_id, command.name);
+        self.run_idempotent(key.as_deref(), fingerprint, self.execute_register_user(command))
+            .await
+    }
+
+    async fn execute_register_user(&self, command: RegisterUserCommand) -> DomainResult<Vec<EventEnvelope>> {
+        let correlation_id = self.id_generator.next_correlation_id();
         
         self.logger.info(&format!(
             "Processing command: RegisterUser(id={}, name={}) [corr_id={}]",
             command.user_id, command.name, correlation_id
         ));
 
-        let user = User::new_with_uniqueness_check(
-            command.user_id,
-            command.name.clone(),
-            self.repository.as_ref(),
-        )?;
-
-        let saved_events = self.repository.save(&user, -1)?;
-
-        for (_index, event) in saved_events.iter().enumerate() {
-            let _envelope = domain::events::EventEnvelope::new(
-                user.id,
-                event.clone(),
-                0,
-                correlation_id.clone(),
-            );
-            
-            match self.event_bus.publish(event).await {
-                Ok(errors) if errors.is_empty() => {},
-                Ok(errors) => {
-                    for err in errors {
-                        self.logger.warn(&format!("Non-critical handler error: {}", err));
-                    }
-                }
-                Err(e) => {
-                    self.logger.error(&format!("Critical error publishing event: {}", e));
-                    return Err(domain::errors::AppError::PublishError(
-                        format!("Failed to publish event: {}", e)
-                    ));
-                }
-            }
-        }
+        let user = self.decide_register_user(&command)?;
+
+        let metadata = EventMetadata {
+            actor: command.actor.clone(),
+            ..EventMetadata::new(correlation_id)
+        };
+        let saved = self.save_and_publish(&user, -1, &metadata).await?;
 
         self.logger
             .info(&format!("User {} registered successfully", command.user_id));
 
-        Ok(())
+        Ok(saved)
+    }
+
+    /// Rename a user; returns the stored envelopes
+    pub async fn handle_rename_user(&self, command: RenameUserCommand) -> DomainResult<Vec<EventEnvelope>> {
+        let key = command.idempotency_key.clone();
+        let fingerprint = format!("RenameUser:{}:{}", command.user_id, command.new_name);
+        self.run_idempotent(key.as_deref(), fingerprint, self.execute_rename_user(command))
+            .await
     }
 
-    pub async fn handle_rename_user(&self, command: RenameUserCommand) -> DomainResult<()> {
-        let correlation_id = generate_correlation_id();
+    async fn execute_rename_user(&self, command: RenameUserCommand) -> DomainResult<Vec<EventEnvelope>> {
+        let correlation_id = self.id_generator.next_correlation_id();
         
         self.logger.info(&format!(
             "Processing command: RenameUser(id={}, new_name={}) [corr_id={}]",
             command.user_id, command.new_name, correlation_id
         ));
 
-        let mut user = self.repository.get_by_id(command.user_id)?;
+        let user = self.decide_rename_user(&command)?;
+
+        let metadata = EventMetadata {
+            actor: command.actor.clone(),
+            ..EventMetadata::new(correlation_id)
+        };
+        let saved = self.save_and_publish(&user, user.version, &metadata).await?;
+
+        self.logger
+            .info(&format!("User {} renamed successfully", command.user_id));
+
+        Ok(saved)
+    }
+
+    /// Revert the most recent change to a user with a compensating event
+    /// Returns the stored compensating envelope; its causation ID is the
+    /// ID of the event it reverts.
+    pub async fn handle_undo_last_change(
+        &self,
+        command: UndoLastChangeCommand,
+    ) -> DomainResult<EventEnvelope> {
+        let correlation_id = self.id_generator.next_correlation_id();
+
+        self.logger.info(&format!(
+            "Processing command: UndoLastChange(id={}, actor={}, reason={}) [corr_id={}]",
+            command.user_id,
+            command.actor.as_deref().unwrap_or("-"),
+            command.reason.as_deref().unwrap_or("-"),
+            correlation_id
+        ));
+
+        let stream = self.repository.get_stream(command.user_id)?;
+        let history: Vec<UserEvent> = stream.iter().map(|envelope| envelope.event.clone()).collect();
+        let reverted_id = stream
+            .last()
+            .map(|envelope| envelope.event_id())
+            .ok_or(domain::errors::AppError::AggregateNotFound(command.user_id))?;
+
+        let mut user = User::load_from_history(history.clone())?;
+        user.undo_last_change(&history, self.clock.as_ref())?;
+
+        // The restored name may have been claimed by someone else since
+        if let Some(existing) = self.repository.find_by_name(&user.name)? {
+            if existing.id != user.id {
+                return Err(domain::errors::AppError::Validation(format!(
+                    "Cannot undo: username '{}' is now taken by user ID {}",
+                    user.name, existing.id
+                )));
+            }
+        }
+
+        let mut metadata = EventMetadata {
+            actor: command.actor,
+            ..EventMetadata::new(correlation_id).with_causation_id(reverted_id.clone())
+        };
+        if let Some(reason) = command.reason {
+            metadata = metadata.with_annotation("reason", reason);
+        }
+
+        let saved = self.save_and_publish(&user, user.version, &metadata).await?;
+        let compensating = saved.into_iter().next().ok_or_else(|| {
+            domain::errors::AppError::RepositoryError("Compensating event was not stored".to_string())
+        })?;
+
+        self.logger.info(&format!(
+            "User {}: reverted {} with {}",
+            command.user_id,
+            reverted_id,
+            compensating.event_id()
+        ));
+
+        Ok(compensating)
+    }
+
+    /// Merge a duplicate account into the surviving one
+    /// The source stream records MergedInto and the target records Absorbed
+    /// with the source event as its cause. Both are stored in one batch
+    /// before either is published, so a merge is never half-applied. Returns
+    /// the stored envelopes, source first.
+    pub async fn handle_merge_users(&self, command: MergeUsersCommand) -> DomainResult<Vec<EventEnvelope>> {
+        let correlation_id = self.id_generator.next_correlation_id();
+
+        self.logger.info(&format!(
+            "Processing command: MergeUsers(source={}, target={}) [corr_id={}]",
+            command.source_id, command.target_id, correlation_id
+        ));
+
+        let mut source = self.repository.get_by_id(command.source_id)?;
+        let mut target = self.repository.get_by_id(command.target_id)?;
 
-        user.rename(command.new_name.clone())?;
+        source.merge_into(&mut target, self.clock.as_ref())?;
 
-        let saved_events = self.repository.save(&user, user.version)?;
+        let metadata = EventMetadata {
+            actor: command.actor,
+            ..EventMetadata::new(correlation_id)
+        };
+        // The batch only commits if the source is still at its loaded version,
+        // so MergedInto is stored as the next version
+        let cause = EventEnvelope::event_id_for("User", source.id, source.version + 1);
+        let absorbed_metadata = metadata.clone().with_causation_id(cause);
+        let saved = self
+            .repository
+            .save_all_with_metadata(&[(&source, &metadata), (&target, &absorbed_metadata)])?;
+        let saved = saved.concat();
+        self.publish_all(&saved).await?;
+
+        self.logger.info(&format!(
+            "User {} merged into user {}",
+            command.source_id, command.target_id
+        ));
+
+        Ok(saved)
+    }
+
+    /// Report the events RegisterUser would produce, without saving or publishing
+    pub fn dry_run_register_user(&self, command: RegisterUserCommand) -> DomainResult<Vec<UserEvent>> {
+        self.logger.info(&format!(
+            "Dry run: RegisterUser(id={}, name={})",
+            command.user_id, command.name
+        ));
+        self.decide_register_user(&command)
+            .map(|user| user.get_uncommitted_changes())
+    }
+
+    /// Report the events RenameUser would produce, without saving or publishing
+    pub fn dry_run_rename_user(&self, command: RenameUserCommand) -> DomainResult<Vec<UserEvent>> {
+        self.logger.info(&format!(
+            "Dry run: RenameUser(id={}, new_name={})",
+            command.user_id, command.new_name
+        ));
+        self.decide_rename_user(&command)
+            .map(|user| user.get_uncommitted_changes())
+    }
 
-        for (_index, event) in saved_events.iter().enumerate() {
-            let _envelope = domain::events::EventEnvelope::new(
-                user.id,
-                event.clone(),
-                user.version,
-                correlation_id.clone(),
-            );
-            
-            match self.event_bus.publish(event).await {
+    /// Validation, uniqueness and aggregate behaviour for RegisterUser
+    fn decide_register_user(&self, command: &RegisterUserCommand) -> DomainResult<User> {
+        User::new_with_uniqueness_check(
+            command.user_id,
+            command.name.clone(),
+            self.repository.as_ref(),
+            self.clock.as_ref(),
+        )
+    }
+
+    /// Load the aggregate and apply the rename, leaving changes uncommitted
+    fn decide_rename_user(&self, command: &RenameUserCommand) -> DomainResult<User> {
+        let mut user = self.repository.get_by_id(command.user_id)?;
+        user.rename(command.new_name.clone(), self.clock.as_ref())?;
+        Ok(user)
+    }
+
+    async fn save_and_publish(
+        &self,
+        user: &User,
+        expected_version: i32,
+        metadata: &EventMetadata,
+    ) -> DomainResult<Vec<EventEnvelope>> {
+        let saved = self.repository.save_with_metadata(user, expected_version, metadata)?;
+        self.publish_all(&saved).await?;
+        Ok(saved)
+    }
+
+    /// Publish stored envelopes in order, failing on a critical handler error
+    async fn publish_all(&self, saved: &[EventEnvelope]) -> DomainResult<()> {
+        for envelope in saved {
+            match self.event_bus.publish_envelope(envelope).await {
                 Ok(errors) if errors.is_empty() => {},
                 Ok(errors) => {
                     for err in errors {
@@ -117,9 +316,34 @@ impl UserCommandHandler {
             }
         }
 
-        self.logger
-            .info(&format!("User {} renamed successfully", command.user_id));
-
         Ok(())
     }
 }
+
+#[async_trait]
+impl Dispatch<RegisterUserCommand> for UserCommandHandler {
+    async fn dispatch(&self, command: RegisterUserCommand) -> DomainResult<()> {
+        self.handle_register_user(command).await.map(|_| ())
+    }
+}
+
+#[async_trait]
+impl Dispatch<RenameUserCommand> for UserCommandHandler {
+    async fn dispatch(&self, command: RenameUserCommand) -> DomainResult<()> {
+        self.handle_rename_user(command).await.map(|_| ())
+    }
+}
+
+#[async_trait]
+impl Dispatch<UndoLastChangeCommand> for UserCommandHandler {
+    async fn dispatch(&self, command: UndoLastChangeCommand) -> DomainResult<()> {
+        self.handle_undo_last_change(command).await.map(|_| ())
+    }
+}
+
+#[async_trait]
+impl Dispatch<MergeUsersCommand> for UserCommandHandler {
+    async fn dispatch(&self, command: MergeUsersCommand) -> DomainResult<()> {
+        self.handle_merge_users(command).await.map(|_| ())
+    }
+}
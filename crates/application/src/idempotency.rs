@@ -0,0 +1,223 @@
+// Idempotency - Remembers command outcomes per client-supplied key
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use chrono::Duration;
+use tokio::sync::OnceCell;
+use domain::errors::{AppError, DomainResult};
+use domain::events::EventEnvelope;
+
+/// Result of a command: the envelopes it stored
+pub type CommandOutcome = DomainResult<Vec<EventEnvelope>>;
+
+/// Outcome of the execution under a key; unset while it is still running
+type Outcome = Arc<OnceCell<CommandOutcome>>;
+
+/// IdempotencyRecord - The first execution under a key
+#[derive(Debug, Clone)]
+struct IdempotencyRecord {
+    fingerprint: String,
+    outcome: Outcome,
+    recorded_at: i64,
+}
+
+/// IdempotencyStore - Keyed outcomes kept for a retention window
+///
+/// A key is reserved before its command executes, so concurrent retries
+/// wait for the first execution instead of racing it. Successes and
+/// validation or domain errors are recorded: a retry gets them back.
+/// Transient failures (conflicts, storage or publish errors) release the
+/// key so a retry runs the command again, as does an execution cancelled
+/// before finishing.
+pub struct IdempotencyStore {
+    records: Mutex<HashMap<String, IdempotencyRecord>>,
+    retention: Duration,
+}
+
+impl IdempotencyStore {
+    pub fn new(retention: Duration) -> Self {
+        IdempotencyStore {
+            records: Mutex::new(HashMap::new()),
+            retention,
+        }
+    }
+
+    /// Execute at most once per key, or wait for and return the outcome of
+    /// the execution that already claimed it
+    /// Returns the outcome and whether it was replayed rather than executed
+    /// here. Fails if the key was first used for a different command.
+    pub async fn run<F>(
+        &self,
+        key: &str,
+        fingerprint: &str,
+        now_millis: i64,
+        execute: F,
+    ) -> DomainResult<(CommandOutcome, bool)>
+    where
+        F: Future<Output = CommandOutcome>,
+    {
+        let outcome = self.reserve(key, fingerprint, now_millis)?;
+        let mut executed = false;
+        let result = outcome
+            .get_or_try_init(|| {
+                executed = true;
+                async {
+                    let result = execute.await;
+                    match result {
+                        Err(err) if !is_deterministic(&err) => Err(err),
+                        result => Ok(result),
+                    }
+                }
+            })
+            .await;
+        match result {
+            Ok(result) => Ok((result.clone(), !executed)),
+            Err(err) => {
+                self.release(key, &outcome)?;
+                Ok((Err(err), false))
+            }
+        }
+    }
+
+    /// The outcome slot for a key, claiming the key if it is unused
+    fn reserve(&self, key: &str, fingerprint: &str, now_millis: i64) -> DomainResult<Outcome> {
+        let mut records = self.records.lock().map_err(|_| AppError::LockPoisoned)?;
+        self.purge_expired(&mut records, now_millis);
+
+        match records.get(key) {
+            Some(record) if record.fingerprint != fingerprint => Err(Self::reused(key)),
+            Some(record) => Ok(record.outcome.clone()),
+            None => {
+                let outcome = Outcome::default();
+                records.insert(
+                    key.to_string(),
+                    IdempotencyRecord {
+                        fingerprint: fingerprint.to_string(),
+                        outcome: outcome.clone(),
+                        recorded_at: now_millis,
+                    },
+                );
+                Ok(outcome)
+            }
+        }
+    }
+
+    /// Forget a key whose execution left no outcome, unless it was since
+    /// reserved again
+    fn release(&self, key: &str, outcome: &Outcome) -> DomainResult<()> {
+        let mut records = self.records.lock().map_err(|_| AppError::LockPoisoned)?;
+        if records
+            .get(key)
+            .is_some_and(|record| Arc::ptr_eq(&record.outcome, outcome) && !outcome.initialized())
+        {
+            records.remove(key);
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.lock().map(|r| r.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn reused(key: &str) -> AppError {
+        AppError::Validation(format!(
+            "Idempotency key '{}' was already used for a different command",
+            key
+        ))
+    }
+
+    fn purge_expired(&self, records: &mut HashMap<String, IdempotencyRecord>, now_millis: i64) {
+        let retention = self.retention.num_milliseconds();
+        records.retain(|_, record| now_millis - record.recorded_at < retention);
+    }
+}
+
+/// Whether running the command again would fail the same way
+fn is_deterministic(err: &AppError) -> bool {
+    matches!(
+        err,
+        AppError::Validation(_) | AppError::AggregateNotFound(_) | AppError::EventReconstructionFailed(_)
+    )
+}
+
+impl Default for IdempotencyStore {
+    /// Keys are remembered for 24 hours
+    fn default() -> Self {
+        Self::new(Duration::hours(24))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &str = "RegisterUser:1:Alice";
+
+    async fn run(
+        store: &IdempotencyStore,
+        fingerprint: &str,
+        now_millis: i64,
+        outcome: CommandOutcome,
+    ) -> DomainResult<(CommandOutcome, bool)> {
+        store.run("key-1", fingerprint, now_millis, async { outcome }).await
+    }
+
+    #[tokio::test]
+    async fn test_run_executes_once_and_replays_the_outcome() {
+        let store = IdempotencyStore::default();
+
+        let first = run(&store, ALICE, 1_000, Ok(Vec::new())).await;
+        let retry = store.run("key-1", ALICE, 2_000, async { panic!("Retry must not execute") }).await;
+
+        assert_eq!(first, Ok((Ok(Vec::new()), false)));
+        assert_eq!(retry, Ok((Ok(Vec::new()), true)));
+    }
+
+    #[tokio::test]
+    async fn test_key_reuse_with_different_command_is_rejected() {
+        let store = IdempotencyStore::default();
+        run(&store, ALICE, 1_000, Ok(Vec::new())).await.unwrap().0.unwrap();
+
+        let result = run(&store, "RegisterUser:2:Bob", 2_000, Ok(Vec::new())).await;
+
+        assert!(matches!(result, Err(AppError::Validation(msg)) if msg.contains("key-1")));
+    }
+
+    #[tokio::test]
+    async fn test_records_expire_after_retention_window() {
+        let store = IdempotencyStore::new(Duration::milliseconds(500));
+        run(&store, ALICE, 1_000, Ok(Vec::new())).await.unwrap().0.unwrap();
+
+        assert_eq!(run(&store, ALICE, 1_499, Err(AppError::LockPoisoned)).await, Ok((Ok(Vec::new()), true)));
+        assert_eq!(run(&store, ALICE, 1_500, Ok(Vec::new())).await, Ok((Ok(Vec::new()), false)));
+    }
+
+    #[tokio::test]
+    async fn test_validation_failures_are_recorded() {
+        let store = IdempotencyStore::default();
+        let failure: CommandOutcome = Err(AppError::Validation("taken".to_string()));
+
+        run(&store, ALICE, 1_000, failure.clone()).await.unwrap().0.unwrap_err();
+        let retry = run(&store, ALICE, 2_000, Ok(Vec::new())).await;
+
+        assert_eq!(retry, Ok((failure, true)));
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_release_the_key() {
+        let store = IdempotencyStore::default();
+        let conflict = AppError::ConcurrencyViolation { expected_version: 0, actual_version: 1 };
+
+        let first = run(&store, ALICE, 1_000, Err(conflict.clone())).await;
+        assert!(store.is_empty(), "Nothing is remembered for a transient failure");
+        let retry = run(&store, ALICE, 2_000, Ok(Vec::new())).await;
+
+        assert_eq!(first, Ok((Err(conflict), false)));
+        assert_eq!(retry, Ok((Ok(Vec::new()), false)), "The retry executes");
+        assert_eq!(store.len(), 1);
+    }
+}
@@ -2,7 +2,21 @@
 pub mod handlers;
 pub mod event_bus;
 pub mod projection_handler;
+pub mod projection_registry;
+pub mod retry;
+pub mod correlation;
+pub mod idempotency;
+#[cfg(any(test, feature = "testing"))]
+pub mod simulation;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
-pub use handlers::UserCommandHandler;
-pub use event_bus::{EventBus, EventHandler, HandlerPriority, PublishError, HandlerError};
-pub use projection_handler::ProjectionEventHandler;
+pub use handlers::{UserCommandHandler, Dispatch};
+pub use event_bus::{EventBus, EventHandler, HandlerPriority, PublishError, HandlerError, ReplayError, TargetFailure, TargetFailures};
+pub use projection_handler::{ProjectionEventHandler, SearchProjectionEventHandler, SqliteProjectionEventHandler};
+pub use retry::{PermanentError, RetryPolicy};
+pub use projection_registry::{ManagedProjection, ProjectionInfo, ProjectionRegistry, ProjectionStatus};
+pub use correlation::{CorrelationIdGenerator, TimestampIdGenerator, SeededIdGenerator};
+pub use idempotency::IdempotencyStore;
+#[cfg(any(test, feature = "testing"))]
+pub use simulation::Simulation;
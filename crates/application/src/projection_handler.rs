@@ -1,7 +1,7 @@
 // Projection event handler adapter
 use async_trait::async_trait;
-use domain::events::UserEvent;
-use persistence::projections::{UserProjection, Handles, TypedUserProjectionHandler};
+use domain::events::{EventEnvelope, UserEvent};
+use persistence::projections::{UserProjection, UserSearchProjection, SqliteUserProjection, Handles, TypedUserProjectionHandler};
 use crate::event_bus::{EventHandler, HandlerPriority};
 
 /// ProjectionEventHandler - Adapts UserProjection to work with EventBus
@@ -23,6 +23,11 @@ impl EventHandler for ProjectionEventHandler {
         self.handler.handle(event);
         Ok(())
     }
+
+    async fn handle_envelope(&self, envelope: &EventEnvelope) -> Result<(), Box<dyn std::error::Error>> {
+        self.handler.handle(envelope);
+        Ok(())
+    }
     
     fn priority(&self) -> HandlerPriority {
         HandlerPriority::Critical
@@ -32,3 +37,73 @@ impl EventHandler for ProjectionEventHandler {
         "ProjectionEventHandler"
     }
 }
+
+/// SearchProjectionEventHandler - Keeps the user search index up to date
+/// Search is a convenience, so a failure here never fails the command.
+pub struct SearchProjectionEventHandler {
+    search: UserSearchProjection,
+}
+
+impl SearchProjectionEventHandler {
+    pub fn new(search: UserSearchProjection) -> Self {
+        SearchProjectionEventHandler { search }
+    }
+}
+
+#[async_trait]
+impl EventHandler for SearchProjectionEventHandler {
+    async fn handle_event(&self, event: &UserEvent) -> Result<(), Box<dyn std::error::Error>> {
+        self.search.handle(event);
+        Ok(())
+    }
+
+    async fn handle_envelope(&self, envelope: &EventEnvelope) -> Result<(), Box<dyn std::error::Error>> {
+        self.search.handle(envelope);
+        Ok(())
+    }
+
+    fn priority(&self) -> HandlerPriority {
+        HandlerPriority::Normal
+    }
+
+    fn name(&self) -> &str {
+        "SearchProjectionEventHandler"
+    }
+}
+
+/// SqliteProjectionEventHandler - Keeps the SQLite read model up to date
+/// Writes run on the blocking pool. Failures are reported but do not fail
+/// the command; the checkpoint lets a later `catch_up` apply whatever was
+/// missed.
+pub struct SqliteProjectionEventHandler {
+    projection: SqliteUserProjection,
+}
+
+impl SqliteProjectionEventHandler {
+    pub fn new(projection: SqliteUserProjection) -> Self {
+        SqliteProjectionEventHandler { projection }
+    }
+}
+
+#[async_trait]
+impl EventHandler for SqliteProjectionEventHandler {
+    async fn handle_event(&self, event: &UserEvent) -> Result<(), Box<dyn std::error::Error>> {
+        let (projection, event) = (self.projection.clone(), event.clone());
+        tokio::task::spawn_blocking(move || projection.apply_event(&event)).await??;
+        Ok(())
+    }
+
+    async fn handle_envelope(&self, envelope: &EventEnvelope) -> Result<(), Box<dyn std::error::Error>> {
+        let (projection, envelope) = (self.projection.clone(), envelope.clone());
+        tokio::task::spawn_blocking(move || projection.apply(&envelope)).await??;
+        Ok(())
+    }
+
+    fn priority(&self) -> HandlerPriority {
+        HandlerPriority::High
+    }
+
+    fn name(&self) -> &str {
+        "SqliteProjectionEventHandler"
+    }
+}
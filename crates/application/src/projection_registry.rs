@@ -0,0 +1,468 @@
+// Projection registry - Named read models fed and managed in one place
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use domain::errors::{AppError, DomainResult};
+use domain::events::{EventEnvelope, UserEvent};
+use infrastructure::Logger;
+use persistence::EventStore;
+use persistence::projections::{AuditLogProjection, RebuildStatus, SqliteUserProjection, UserProjection, UserSearchProjection, UserStatsProjection};
+use crate::event_bus::{EventHandler, HandlerPriority, TargetFailure, TargetFailures};
+use crate::retry::RetryPolicy;
+
+/// ManagedProjection - A read model the registry can feed, stop and rebuild
+///
+/// Implementations checkpoint by global position, so replaying the log
+/// after a stop or failure applies only what they missed.
+pub trait ManagedProjection: Send + Sync {
+    /// Unique name the projection is registered and controlled under
+    fn name(&self) -> &str;
+
+    /// Version of the data the projection is serving
+    fn version(&self) -> u32 {
+        1
+    }
+
+    /// Global position of the last applied envelope (None before any)
+    fn checkpoint(&self) -> DomainResult<Option<u64>>;
+
+    /// Apply one stored event at most once; returns whether it was applied
+    fn apply(&self, envelope: &EventEnvelope) -> DomainResult<bool>;
+
+    /// Apply every stored event after the checkpoint
+    fn catch_up(&self, event_store: &EventStore) -> DomainResult<usize>;
+
+    /// Discard the projection's state and rebuild it from the whole log
+    fn rebuild(&self, event_store: &EventStore) -> DomainResult<usize>;
+
+    /// Whether the projection is rebuilding on its own (e.g. in the background)
+    fn is_rebuilding(&self) -> bool {
+        false
+    }
+
+    /// Whether applying events does blocking I/O, so live delivery runs it
+    /// on the blocking pool instead of a runtime thread
+    fn blocks(&self) -> bool {
+        false
+    }
+}
+
+/// ProjectionStatus - Whether a registered projection is receiving events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectionStatus {
+    Running,
+    /// Stopped on request; events are replayed from the checkpoint on start
+    Stopped,
+    Rebuilding,
+    /// Applying an event failed; stays out of delivery until restarted
+    Failed,
+}
+
+/// ProjectionInfo - Snapshot of one registered projection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectionInfo {
+    pub name: String,
+    pub version: u32,
+    pub checkpoint: Option<u64>,
+    pub status: ProjectionStatus,
+    /// Stored events not yet applied
+    pub lag: u64,
+    /// Why the projection failed, while it is failed
+    pub last_error: Option<String>,
+}
+
+struct Registration {
+    projection: Arc<dyn ManagedProjection>,
+    status: Mutex<ProjectionStatus>,
+    last_error: Mutex<Option<String>>,
+}
+
+impl Registration {
+    fn set_status(&self, status: ProjectionStatus, error: Option<String>) {
+        *self.status.lock().unwrap() = status;
+        *self.last_error.lock().unwrap() = error;
+    }
+}
+
+/// ProjectionRegistry - Feeds every registered projection from the event bus
+///
+/// Subscribe the registry to the bus once; each projection then receives
+/// stored events while it is running. A projection that fails is marked
+/// failed and skipped without affecting the command or the others; the
+/// bus dead-letters the event under the projection's name.
+#[derive(Clone)]
+pub struct ProjectionRegistry {
+    event_store: EventStore,
+    projections: Arc<Mutex<BTreeMap<String, Arc<Registration>>>>,
+    logger: Arc<dyn Logger>,
+}
+
+impl ProjectionRegistry {
+    pub fn new(event_store: EventStore) -> Self {
+        ProjectionRegistry {
+            event_store,
+            projections: Arc::new(Mutex::new(BTreeMap::new())),
+            logger: Arc::new(infrastructure::ConsoleLogger::default()),
+        }
+    }
+
+    pub fn with_logger(mut self, logger: Arc<dyn Logger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Add a projection, stopped until `start` (or `start_all`) catches it up
+    pub fn register<P: ManagedProjection + 'static>(&self, projection: Arc<P>) -> DomainResult<()> {
+        let mut projections = self.projections.lock().map_err(|_| AppError::LockPoisoned)?;
+        let name = projection.name().to_string();
+        if projections.contains_key(&name) {
+            return Err(AppError::Validation(format!(
+                "A projection named '{}' is already registered",
+                name
+            )));
+        }
+        projections.insert(
+            name,
+            Arc::new(Registration {
+                projection,
+                status: Mutex::new(ProjectionStatus::Stopped),
+                last_error: Mutex::new(None),
+            }),
+        );
+        Ok(())
+    }
+
+    fn registration(&self, name: &str) -> DomainResult<Arc<Registration>> {
+        self.projections
+            .lock()
+            .map_err(|_| AppError::LockPoisoned)?
+            .get(name)
+            .cloned()
+            .ok_or_else(|| AppError::Validation(format!("Unknown projection '{}'", name)))
+    }
+
+    fn registrations(&self) -> Vec<Arc<Registration>> {
+        self.projections.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Names of all registered projections, in order
+    pub fn names(&self) -> Vec<String> {
+        self.projections.lock().unwrap().keys().cloned().collect()
+    }
+
+    /// Catch a projection up from its checkpoint and resume live delivery
+    pub fn start(&self, name: &str) -> DomainResult<ProjectionInfo> {
+        let registration = self.registration(name)?;
+        self.resume(&registration, |projection, store| projection.catch_up(store))?;
+        self.info(name)
+    }
+
+    /// Start every registered projection
+    pub fn start_all(&self) -> DomainResult<()> {
+        self.names().iter().try_for_each(|name| self.start(name).map(|_| ()))
+    }
+
+    /// Stop delivering events to a projection; it keeps serving what it has
+    pub fn stop(&self, name: &str) -> DomainResult<ProjectionInfo> {
+        self.registration(name)?.set_status(ProjectionStatus::Stopped, None);
+        self.logger.info(&format!("Projection '{}' stopped", name));
+        self.info(name)
+    }
+
+    /// Rebuild a projection from the whole log, then resume live delivery
+    pub fn rebuild(&self, name: &str) -> DomainResult<ProjectionInfo> {
+        let registration = self.registration(name)?;
+        registration.set_status(ProjectionStatus::Rebuilding, None);
+        self.logger.info(&format!("Rebuilding projection '{}'", name));
+        self.resume(&registration, |projection, store| projection.rebuild(store))?;
+        self.info(name)
+    }
+
+    /// Run `replay`, then mark the projection running or failed
+    /// Events delivered during the replay were skipped, so it catches up
+    /// once more after switching to running. Applies are at most once, so an
+    /// event racing the switch is applied here or by delivery, never twice.
+    fn resume(
+        &self,
+        registration: &Registration,
+        replay: impl Fn(&dyn ManagedProjection, &EventStore) -> DomainResult<usize>,
+    ) -> DomainResult<()> {
+        let name = registration.projection.name();
+        let result = replay(registration.projection.as_ref(), &self.event_store)
+            .and_then(|_| {
+                registration.set_status(ProjectionStatus::Running, None);
+                registration.projection.catch_up(&self.event_store)
+            });
+        match result {
+            Ok(_) => {
+                self.logger.info(&format!("Projection '{}' running", name));
+                Ok(())
+            }
+            Err(err) => {
+                self.logger.error(&format!("Projection '{}' failed: {}", name, err));
+                registration.set_status(ProjectionStatus::Failed, Some(err.to_string()));
+                Err(err)
+            }
+        }
+    }
+
+    /// Snapshot of one projection
+    pub fn info(&self, name: &str) -> DomainResult<ProjectionInfo> {
+        let registration = self.registration(name)?;
+        Ok(self.describe(name, &registration))
+    }
+
+    /// Snapshot of every projection, in name order
+    pub fn list(&self) -> Vec<ProjectionInfo> {
+        let projections = self.projections.lock().unwrap().clone();
+        projections
+            .iter()
+            .map(|(name, registration)| self.describe(name, registration))
+            .collect()
+    }
+
+    fn describe(&self, name: &str, registration: &Registration) -> ProjectionInfo {
+        let projection = &registration.projection;
+        let mut status = *registration.status.lock().unwrap();
+        let mut last_error = registration.last_error.lock().unwrap().clone();
+        if status == ProjectionStatus::Running && projection.is_rebuilding() {
+            status = ProjectionStatus::Rebuilding;
+        }
+        let checkpoint = projection.checkpoint().unwrap_or_else(|err| {
+            status = ProjectionStatus::Failed;
+            last_error = Some(err.to_string());
+            None
+        });
+        let applied = checkpoint.map_or(0, |position| position + 1);
+
+        ProjectionInfo {
+            name: name.to_string(),
+            version: projection.version(),
+            checkpoint,
+            status,
+            lag: self.event_store.head_position().saturating_sub(applied),
+            last_error,
+        }
+    }
+}
+
+#[async_trait]
+impl EventHandler for ProjectionRegistry {
+    /// Bare events carry no position to checkpoint; only stored events
+    /// published with their envelope are routed to projections
+    async fn handle_event(&self, _event: &UserEvent) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+
+    async fn handle_envelope(&self, envelope: &EventEnvelope) -> Result<(), Box<dyn std::error::Error>> {
+        let mut failed = Vec::new();
+        for registration in self.registrations() {
+            if *registration.status.lock().unwrap() != ProjectionStatus::Running {
+                continue;
+            }
+            if let Err(err) = apply(&registration, envelope).await {
+                let name = registration.projection.name();
+                self.logger.error(&format!(
+                    "Projection '{}' failed at position {}: {}",
+                    name, envelope.global_position, err
+                ));
+                registration.set_status(ProjectionStatus::Failed, Some(err.to_string()));
+                failed.push(TargetFailure {
+                    target: name.to_string(),
+                    error_message: err.to_string(),
+                });
+            }
+        }
+
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(Box::new(TargetFailures(failed)))
+        }
+    }
+
+    /// Replay a dead-lettered event into one projection
+    /// A running projection applies it (or skips it if already applied); a
+    /// failed one restarts from its checkpoint, which includes the event.
+    /// Stopped and rebuilding projections are left alone.
+    async fn redeliver(&self, target: &str, envelope: &EventEnvelope) -> Result<(), Box<dyn std::error::Error>> {
+        let registration = self.registration(target)?;
+        let status = *registration.status.lock().unwrap();
+        match status {
+            ProjectionStatus::Running => {
+                if let Err(err) = apply(&registration, envelope).await {
+                    registration.set_status(ProjectionStatus::Failed, Some(err.to_string()));
+                    return Err(err.into());
+                }
+                Ok(())
+            }
+            ProjectionStatus::Failed => {
+                // Catching up replays the log, so keep it off the runtime threads
+                let (registry, target) = (self.clone(), target.to_string());
+                tokio::task::spawn_blocking(move || registry.start(&target))
+                    .await
+                    .map_err(|err| AppError::RepositoryError(format!("Projection task failed: {}", err)))??;
+                Ok(())
+            }
+            ProjectionStatus::Stopped => {
+                Err(format!("Projection '{}' is stopped; start it to catch up", target).into())
+            }
+            ProjectionStatus::Rebuilding => {
+                Err(format!("Projection '{}' is rebuilding", target).into())
+            }
+        }
+    }
+
+    fn priority(&self) -> HandlerPriority {
+        HandlerPriority::High
+    }
+
+    fn name(&self) -> &str {
+        "ProjectionRegistry"
+    }
+
+    /// A projection that fails is marked failed and skipped, so retrying
+    /// would not reach it; it recovers through `start` or a replay
+    fn retry_policy(&self) -> Option<RetryPolicy> {
+        Some(RetryPolicy::none())
+    }
+}
+
+/// For standalone copies of the user read model; the one commands check
+/// names against is fed by its own Critical handler, never a registry
+/// Apply a live event, on the blocking pool if the projection does I/O
+async fn apply(registration: &Arc<Registration>, envelope: &EventEnvelope) -> DomainResult<bool> {
+    if !registration.projection.blocks() {
+        return registration.projection.apply(envelope);
+    }
+    let (registration, envelope) = (Arc::clone(registration), envelope.clone());
+    tokio::task::spawn_blocking(move || registration.projection.apply(&envelope))
+        .await
+        .map_err(|err| AppError::RepositoryError(format!("Projection task failed: {}", err)))?
+}
+
+impl ManagedProjection for UserProjection {
+    fn name(&self) -> &str {
+        "users"
+    }
+
+    fn version(&self) -> u32 {
+        UserProjection::version(self)
+    }
+
+    fn checkpoint(&self) -> DomainResult<Option<u64>> {
+        Ok(UserProjection::checkpoint(self))
+    }
+
+    fn apply(&self, envelope: &EventEnvelope) -> DomainResult<bool> {
+        Ok(UserProjection::apply(self, envelope))
+    }
+
+    fn catch_up(&self, event_store: &EventStore) -> DomainResult<usize> {
+        Ok(UserProjection::catch_up(self, event_store))
+    }
+
+    fn rebuild(&self, event_store: &EventStore) -> DomainResult<usize> {
+        UserProjection::rebuild(self, event_store).ok_or_else(|| {
+            AppError::Validation("A rebuild of projection 'users' is already running".to_string())
+        })
+    }
+
+    fn is_rebuilding(&self) -> bool {
+        self.rebuild_progress().status == RebuildStatus::Building
+    }
+}
+
+impl ManagedProjection for UserSearchProjection {
+    fn name(&self) -> &str {
+        "user_search"
+    }
+
+    fn checkpoint(&self) -> DomainResult<Option<u64>> {
+        Ok(UserSearchProjection::checkpoint(self))
+    }
+
+    fn apply(&self, envelope: &EventEnvelope) -> DomainResult<bool> {
+        Ok(UserSearchProjection::apply(self, envelope))
+    }
+
+    fn catch_up(&self, event_store: &EventStore) -> DomainResult<usize> {
+        Ok(UserSearchProjection::catch_up(self, event_store))
+    }
+
+    fn rebuild(&self, event_store: &EventStore) -> DomainResult<usize> {
+        Ok(UserSearchProjection::rebuild(self, event_store))
+    }
+}
+
+impl ManagedProjection for UserStatsProjection {
+    fn name(&self) -> &str {
+        "user_stats"
+    }
+
+    fn checkpoint(&self) -> DomainResult<Option<u64>> {
+        Ok(UserStatsProjection::checkpoint(self))
+    }
+
+    fn apply(&self, envelope: &EventEnvelope) -> DomainResult<bool> {
+        Ok(UserStatsProjection::apply(self, envelope))
+    }
+
+    fn catch_up(&self, event_store: &EventStore) -> DomainResult<usize> {
+        Ok(UserStatsProjection::catch_up(self, event_store))
+    }
+
+    fn rebuild(&self, event_store: &EventStore) -> DomainResult<usize> {
+        Ok(UserStatsProjection::rebuild(self, event_store))
+    }
+}
+
+impl ManagedProjection for AuditLogProjection {
+    fn name(&self) -> &str {
+        "audit_log"
+    }
+
+    fn checkpoint(&self) -> DomainResult<Option<u64>> {
+        Ok(AuditLogProjection::checkpoint(self))
+    }
+
+    fn apply(&self, envelope: &EventEnvelope) -> DomainResult<bool> {
+        Ok(AuditLogProjection::apply(self, envelope))
+    }
+
+    fn catch_up(&self, event_store: &EventStore) -> DomainResult<usize> {
+        Ok(AuditLogProjection::catch_up(self, event_store))
+    }
+
+    fn rebuild(&self, event_store: &EventStore) -> DomainResult<usize> {
+        Ok(AuditLogProjection::rebuild(self, event_store))
+    }
+}
+
+impl ManagedProjection for SqliteUserProjection {
+    fn name(&self) -> &str {
+        "users_sqlite"
+    }
+
+    fn checkpoint(&self) -> DomainResult<Option<u64>> {
+        SqliteUserProjection::checkpoint(self)
+    }
+
+    fn apply(&self, envelope: &EventEnvelope) -> DomainResult<bool> {
+        SqliteUserProjection::apply(self, envelope)
+    }
+
+    fn catch_up(&self, event_store: &EventStore) -> DomainResult<usize> {
+        SqliteUserProjection::catch_up(self, event_store)
+    }
+
+    fn rebuild(&self, event_store: &EventStore) -> DomainResult<usize> {
+        SqliteUserProjection::reset(self)?;
+        SqliteUserProjection::attach(self, event_store)?;
+        SqliteUserProjection::catch_up(self, event_store)
+    }
+
+    fn blocks(&self) -> bool {
+        true
+    }
+}
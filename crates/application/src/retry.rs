@@ -0,0 +1,164 @@
+// Retry policies for event handlers
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// PermanentError - A handler failure that retrying cannot fix
+/// The default classification retries every error except this one.
+#[derive(Debug, Clone)]
+pub struct PermanentError(pub String);
+
+impl fmt::Display for PermanentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for PermanentError {}
+
+type Classifier = Arc<dyn Fn(&(dyn std::error::Error + 'static)) -> bool + Send + Sync>;
+
+/// RetryPolicy - How often, and how patiently, a failing handler is retried
+///
+/// The delay before retry `n` is `initial_backoff * multiplier^(n-1)`, capped
+/// at `max_backoff`, then reduced by a random fraction of up to `jitter` so
+/// handlers failing together do not retry in lockstep.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: f64,
+    retryable: Classifier,
+}
+
+impl RetryPolicy {
+    /// Up to `max_attempts` deliveries in total (the first one included)
+    pub fn new(max_attempts: u32) -> Self {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            ..Self::default()
+        }
+    }
+
+    /// Deliver once; a failure is final
+    pub fn none() -> Self {
+        Self::new(1)
+    }
+
+    pub fn with_backoff(mut self, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff.max(initial_backoff);
+        self
+    }
+
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Fraction of each delay (0.0 to 1.0) that may be randomly shaved off
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Decide which errors are worth retrying
+    pub fn with_retryable<F>(mut self, retryable: F) -> Self
+    where
+        F: Fn(&(dyn std::error::Error + 'static)) -> bool + Send + Sync + 'static,
+    {
+        self.retryable = Arc::new(retryable);
+        self
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub fn is_retryable(&self, error: &(dyn std::error::Error + 'static)) -> bool {
+        (self.retryable)(error)
+    }
+
+    /// Delay before retry number `retry` (1 = the first retry)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay = self
+            .initial_backoff
+            .mul_f64(self.multiplier.powi(exponent).min(u32::MAX as f64))
+            .min(self.max_backoff);
+        delay.mul_f64(1.0 - self.jitter * random_fraction())
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, backing off from 50ms up to 2s with 50% jitter
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(2),
+            multiplier: 2.0,
+            jitter: 0.5,
+            retryable: Arc::new(|error| !error.is::<PermanentError>()),
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("multiplier", &self.multiplier)
+            .field("jitter", &self.jitter)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Uniform value in [0, 1), from std's randomly keyed hasher
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_exponentially_up_to_the_cap() {
+        let policy = RetryPolicy::new(5)
+            .with_backoff(Duration::from_millis(100), Duration::from_millis(500))
+            .with_jitter(0.0);
+
+        let delays: Vec<u128> = (1..=5).map(|retry| policy.backoff(retry).as_millis()).collect();
+
+        assert_eq!(delays, vec![100, 200, 400, 500, 500]);
+    }
+
+    #[test]
+    fn test_jitter_only_shortens_the_delay() {
+        let policy = RetryPolicy::new(3)
+            .with_backoff(Duration::from_millis(100), Duration::from_secs(1))
+            .with_jitter(0.5);
+
+        for _ in 0..100 {
+            let delay = policy.backoff(2);
+            assert!(delay > Duration::from_millis(100) && delay <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn test_permanent_errors_are_not_retryable() {
+        let policy = RetryPolicy::default();
+        let transient: Box<dyn std::error::Error> = "connection reset".into();
+
+        assert!(policy.is_retryable(transient.as_ref()));
+        assert!(!policy.is_retryable(&PermanentError("bad data".to_string())));
+    }
+}
@@ -0,0 +1,131 @@
+// Deterministic simulation harness for end-to-end scenarios
+use std::sync::Arc;
+use domain::{
+    clock::ManualClock,
+    commands::{RegisterUserCommand, RenameUserCommand},
+    errors::DomainResult,
+    events::UserEvent,
+};
+use infrastructure::MockLogger;
+use persistence::{EventStore, Repository, UserProjection};
+use crate::{EventBus, ProjectionEventHandler, SeededIdGenerator, UserCommandHandler};
+
+/// Simulation - Fully wired CQRS stack with no sources of nondeterminism
+///
+/// Timestamps come from a virtual clock that only moves when a command runs
+/// (by `tick_millis`) or when the scenario advances it explicitly. Correlation
+/// IDs come from a seeded generator. Running the same scenario with the same
+/// seed therefore produces byte-identical event logs.
+pub struct Simulation {
+    clock: ManualClock,
+    event_store: EventStore,
+    projection: UserProjection,
+    repository: Arc<Repository>,
+    event_bus: EventBus,
+    logger: Arc<MockLogger>,
+    command_handler: UserCommandHandler,
+    tick_millis: i64,
+}
+
+impl Simulation {
+    /// Virtual epoch every simulation starts from (2023-11-14T22:13:20Z)
+    pub const START_MILLIS: i64 = 1_700_000_000_000;
+
+    pub fn new(seed: u64) -> Self {
+        let clock = ManualClock::from_millis(Self::START_MILLIS);
+        let logger = Arc::new(MockLogger::new());
+        let event_store = EventStore::new().with_clock(Arc::new(clock.clone()));
+        let projection = UserProjection::new();
+        let event_bus = EventBus::new()
+            .with_logger(logger.clone())
+            .with_dead_letter_queue(event_store.clone());
+        event_bus.subscribe(Arc::new(ProjectionEventHandler::new(projection.clone())));
+
+        let repository = Arc::new(Repository::new(event_store.clone(), projection.clone()));
+        let command_handler = UserCommandHandler::new(repository.clone(), event_bus.clone(), logger.clone())
+            .with_clock(Arc::new(clock.clone()))
+            .with_id_generator(Arc::new(SeededIdGenerator::new(seed)));
+
+        Simulation {
+            clock,
+            event_store,
+            projection,
+            repository,
+            event_bus,
+            logger,
+            command_handler,
+            tick_millis: 1_000,
+        }
+    }
+
+    /// How far the virtual clock moves after each command (default 1s)
+    pub fn with_tick_millis(mut self, tick_millis: i64) -> Self {
+        self.tick_millis = tick_millis;
+        self
+    }
+
+    pub fn command_handler(&self) -> &UserCommandHandler {
+        &self.command_handler
+    }
+
+    pub fn event_store(&self) -> &EventStore {
+        &self.event_store
+    }
+
+    pub fn projection(&self) -> &UserProjection {
+        &self.projection
+    }
+
+    pub fn repository(&self) -> &Repository {
+        &self.repository
+    }
+
+    pub fn event_bus(&self) -> &EventBus {
+        &self.event_bus
+    }
+
+    pub fn clock(&self) -> &ManualClock {
+        &self.clock
+    }
+
+    pub fn advance_millis(&self, millis: i64) {
+        self.clock.advance_millis(millis);
+    }
+
+    /// Seed prior history straight into the store and read model
+    /// Bypasses command handling and the event bus, like a restored backup.
+    pub fn load_history(&self, events: Vec<UserEvent>) {
+        for event in events {
+            self.event_store.append(event.aggregate_id(), event);
+        }
+        self.projection.catch_up(&self.event_store);
+    }
+
+    pub async fn register_user(&self, user_id: u32, name: &str) -> DomainResult<()> {
+        let command = RegisterUserCommand::new(user_id, name.to_string())?;
+        let result = self.command_handler.handle_register_user(command).await.map(|_| ());
+        self.clock.advance_millis(self.tick_millis);
+        result
+    }
+
+    pub async fn rename_user(&self, user_id: u32, new_name: &str) -> DomainResult<()> {
+        let command = RenameUserCommand::new(user_id, new_name.to_string())?;
+        let result = self.command_handler.handle_rename_user(command).await.map(|_| ());
+        self.clock.advance_millis(self.tick_millis);
+        result
+    }
+
+    /// Every stored event, one per line, in deterministic order
+    pub fn event_log(&self) -> String {
+        self.event_store
+            .get_all_events()
+            .iter()
+            .map(|event| format!("{}\n", event))
+            .collect()
+    }
+
+    /// Every message logged by the wired components, including correlation IDs
+    pub fn log_messages(&self) -> Vec<String> {
+        self.logger.get_messages_as_strings()
+    }
+}
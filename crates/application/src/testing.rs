@@ -0,0 +1,123 @@
+// Test support - Given/When/Then scenarios against UserCommandHandler
+//
+//     given(vec![registered_event])
+//         .when(RenameUserCommand::new(1, "Bob".to_string())?)
+//         .await
+//         .then_expect(vec![renamed_event]);
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use domain::errors::{AppError, DomainResult};
+use domain::events::UserEvent;
+use domain::testing::{diff_events, NOW};
+use crate::event_bus::{EventHandler, HandlerPriority};
+use crate::handlers::Dispatch;
+use crate::{Simulation, UserCommandHandler};
+
+/// Start a handler scenario from the given event history
+pub fn given(events: Vec<UserEvent>) -> HandlerGiven {
+    HandlerGiven {
+        history: events,
+        now: NOW,
+    }
+}
+
+/// HandlerGiven - Prior history loaded into a fresh simulated stack
+pub struct HandlerGiven {
+    history: Vec<UserEvent>,
+    now: i64,
+}
+
+impl HandlerGiven {
+    /// Run the command at this instant instead of NOW
+    pub fn at(mut self, millis: i64) -> Self {
+        self.now = millis;
+        self
+    }
+
+    /// Dispatch the command through the full command handler
+    pub async fn when<C>(self, command: C) -> HandlerThen
+    where
+        UserCommandHandler: Dispatch<C>,
+    {
+        let simulation = Simulation::new(0);
+        simulation.load_history(self.history);
+        simulation.clock().set_millis(self.now);
+
+        let recorder = Arc::new(RecordingHandler::default());
+        simulation.event_bus().subscribe(recorder.clone());
+
+        let outcome = simulation.command_handler().dispatch(command).await;
+        let produced = recorder.events.lock().unwrap().clone();
+
+        HandlerThen {
+            outcome,
+            produced,
+            simulation,
+        }
+    }
+}
+
+/// HandlerThen - Outcome of a handler scenario
+pub struct HandlerThen {
+    outcome: DomainResult<()>,
+    produced: Vec<UserEvent>,
+    simulation: Simulation,
+}
+
+impl HandlerThen {
+    /// Assert the command succeeded and published exactly these events
+    pub fn then_expect(self, expected: Vec<UserEvent>) -> Self {
+        if let Err(err) = &self.outcome {
+            panic!(
+                "Expected events but command failed:\n  error: {:?}\n  expected: {:#?}",
+                err, expected
+            );
+        }
+        if self.produced != expected {
+            panic!("{}", diff_events(&expected, &self.produced));
+        }
+        self
+    }
+
+    /// Assert the command failed with exactly this error
+    pub fn then_error(self, expected: AppError) -> Self {
+        match &self.outcome {
+            Ok(()) => panic!(
+                "Expected error but command succeeded:\n  expected: {:?}\n  produced: {:#?}",
+                expected, self.produced
+            ),
+            Err(actual) if *actual != expected => panic!(
+                "Error mismatch:\n- expected: {:?}\n+ actual:   {:?}",
+                expected, actual
+            ),
+            Err(_) => self,
+        }
+    }
+
+    /// The stack the scenario ran against, for read-side assertions
+    pub fn simulation(&self) -> &Simulation {
+        &self.simulation
+    }
+}
+
+/// RecordingHandler - Captures every event published during the scenario
+#[derive(Default)]
+struct RecordingHandler {
+    events: Mutex<Vec<UserEvent>>,
+}
+
+#[async_trait]
+impl EventHandler for RecordingHandler {
+    async fn handle_event(&self, event: &UserEvent) -> Result<(), Box<dyn std::error::Error>> {
+        self.events.lock().unwrap().push(event.clone());
+        Ok(())
+    }
+
+    fn priority(&self) -> HandlerPriority {
+        HandlerPriority::Critical
+    }
+
+    fn name(&self) -> &str {
+        "ScenarioRecorder"
+    }
+}
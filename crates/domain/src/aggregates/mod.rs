@@ -2,14 +2,32 @@
 // Encapsulates state and business logic for the User domain concept
 use crate::events::UserEvent;
 use crate::errors::DomainResult;
+use crate::clock::{Clock, SystemClock};
+use crate::commands::{RegisterUserCommand, RenameUserCommand};
 use std::fmt;
 
+/// Aggregate - Event-sourced entity that can be rebuilt from its history
+pub trait Aggregate: Sized {
+    type Event: Clone + PartialEq + fmt::Debug;
+
+    fn load_from_history(events: Vec<Self::Event>) -> DomainResult<Self>;
+    fn get_uncommitted_changes(&self) -> Vec<Self::Event>;
+}
+
+/// Execute<C> - Decision logic of an aggregate for command C
+/// Successful execution records new events as uncommitted changes.
+pub trait Execute<C>: Aggregate {
+    fn execute(&mut self, command: C, clock: &dyn Clock) -> DomainResult<()>;
+}
+
 /// User Aggregate - Encapsulates both state and business logic
 #[derive(Clone)]
 pub struct User {
     pub id: u32,
     pub name: String,
     pub version: i32,
+    /// Set once this account has been merged into another user
+    pub merged_into: Option<u32>,
     uncommitted_changes: Vec<UserEvent>,
 }
 
@@ -19,6 +37,7 @@ impl fmt::Debug for User {
             .field("id", &self.id)
             .field("name", &self.name)
             .field("version", &self.version)
+            .field("merged_into", &self.merged_into)
             .field("uncommitted_changes", &format!("<{} events>", self.uncommitted_changes.len()))
             .finish()
     }
@@ -31,6 +50,7 @@ impl User {
         id: u32,
         name: String,
         repository: &dyn crate::repository::IRepository,
+        clock: &dyn Clock,
     ) -> DomainResult<Self> {
         // Validate invariants
         if id == 0 {
@@ -65,13 +85,14 @@ impl User {
             id,
             name: String::new(),
             version: -1,
+            merged_into: None,
             uncommitted_changes: Vec::new(),
         };
 
         let event = UserEvent::Registered {
             user_id: id,
             name,
-            timestamp: chrono::Utc::now().timestamp_millis(),
+            timestamp: clock.now_millis(),
         };
 
         user.apply_event(&event);
@@ -83,6 +104,11 @@ impl User {
     /// Create a new user with value constraint validation only
     /// For testing and event sourcing reconstruction
     pub fn new(id: u32, name: String) -> DomainResult<Self> {
+        Self::new_with_clock(id, name, &SystemClock)
+    }
+
+    /// Create a new user, stamping the Registered event with the given clock
+    pub fn new_with_clock(id: u32, name: String, clock: &dyn Clock) -> DomainResult<Self> {
         if id == 0 {
             return Err(crate::errors::AppError::Validation(
                 "User ID must be greater than 0".to_string(),
@@ -105,13 +131,14 @@ impl User {
             id,
             name: String::new(),
             version: -1,
+            merged_into: None,
             uncommitted_changes: Vec::new(),
         };
 
         let event = UserEvent::Registered {
             user_id: id,
             name,
-            timestamp: chrono::Utc::now().timestamp_millis(),
+            timestamp: clock.now_millis(),
         };
 
         user.apply_event(&event);
@@ -138,6 +165,10 @@ impl User {
             } => {
                 self.name = new_name.clone();
             }
+            UserEvent::MergedInto { target_id, .. } => {
+                self.merged_into = Some(*target_id);
+            }
+            UserEvent::Absorbed { .. } => {}
         }
     }
 
@@ -147,6 +178,7 @@ impl User {
             id: 0,
             name: String::new(),
             version: -1,
+            merged_into: None,
             uncommitted_changes: Vec::new(),
         };
 
@@ -167,7 +199,9 @@ impl User {
     }
 
     /// Rename the user with validation
-    pub fn rename(&mut self, new_name: String) -> DomainResult<()> {
+    pub fn rename(&mut self, new_name: String, clock: &dyn Clock) -> DomainResult<()> {
+        self.ensure_not_merged()?;
+
         if new_name.trim().is_empty() {
             return Err(crate::errors::AppError::Validation(
                 "New name cannot be empty".to_string(),
@@ -183,7 +217,7 @@ impl User {
         let event = UserEvent::Renamed {
             user_id: self.id,
             new_name,
-            timestamp: chrono::Utc::now().timestamp_millis(),
+            timestamp: clock.now_millis(),
         };
 
         self.apply_event(&event);
@@ -191,4 +225,113 @@ impl User {
         
         Ok(())
     }
+
+    /// Record the compensating event for the last entry of `history`
+    /// `history` must be the stream this aggregate was loaded from.
+    pub fn undo_last_change(&mut self, history: &[UserEvent], clock: &dyn Clock) -> DomainResult<()> {
+        let (last, earlier) = match history.split_last() {
+            Some(split) => split,
+            None => return Err(crate::errors::AppError::AggregateNotFound(self.id)),
+        };
+
+        if earlier.is_empty() {
+            return Err(crate::errors::AppError::Validation(format!(
+                "Nothing to undo: user {} has no changes since registration",
+                self.id
+            )));
+        }
+
+        match last {
+            // Both a rename and a re-registration changed the name; restore it
+            UserEvent::Renamed { .. } | UserEvent::Registered { .. } => {
+                let previous = User::load_from_history(earlier.to_vec())?;
+                self.rename(previous.name, clock)
+            }
+            UserEvent::MergedInto { .. } | UserEvent::Absorbed { .. } => {
+                Err(crate::errors::AppError::Validation(format!(
+                    "Cannot undo: the last change to user {} was a merge",
+                    self.id
+                )))
+            }
+        }
+    }
+
+    /// Merge this (duplicate) account into `target`
+    /// Records MergedInto on this user and Absorbed on the target.
+    pub fn merge_into(&mut self, target: &mut User, clock: &dyn Clock) -> DomainResult<()> {
+        if self.id == target.id {
+            return Err(crate::errors::AppError::Validation(
+                "Cannot merge a user into itself".to_string(),
+            ));
+        }
+        self.ensure_not_merged()?;
+        target.ensure_not_merged()?;
+
+        let timestamp = clock.now_millis();
+        let merged = UserEvent::MergedInto {
+            user_id: self.id,
+            target_id: target.id,
+            timestamp,
+        };
+        let absorbed = UserEvent::Absorbed {
+            user_id: target.id,
+            source_id: self.id,
+            timestamp,
+        };
+
+        self.apply_event(&merged);
+        self.uncommitted_changes.push(merged);
+        target.apply_event(&absorbed);
+        target.uncommitted_changes.push(absorbed);
+
+        Ok(())
+    }
+
+    fn ensure_not_merged(&self) -> DomainResult<()> {
+        match self.merged_into {
+            Some(target_id) => Err(crate::errors::AppError::Validation(format!(
+                "User {} was merged into user {}",
+                self.id, target_id
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Aggregate for User {
+    type Event = UserEvent;
+
+    fn load_from_history(events: Vec<UserEvent>) -> DomainResult<Self> {
+        User::load_from_history(events)
+    }
+
+    fn get_uncommitted_changes(&self) -> Vec<UserEvent> {
+        User::get_uncommitted_changes(self)
+    }
+}
+
+impl Execute<RegisterUserCommand> for User {
+    fn execute(&mut self, command: RegisterUserCommand, clock: &dyn Clock) -> DomainResult<()> {
+        if self.version != -1 || !self.uncommitted_changes.is_empty() {
+            return Err(crate::errors::AppError::Validation(format!(
+                "User {} is already registered",
+                self.id
+            )));
+        }
+        let registered = User::new_with_clock(command.user_id, command.name, clock)?;
+        for event in registered.uncommitted_changes {
+            self.apply_event(&event);
+            self.uncommitted_changes.push(event);
+        }
+        Ok(())
+    }
+}
+
+impl Execute<RenameUserCommand> for User {
+    fn execute(&mut self, command: RenameUserCommand, clock: &dyn Clock) -> DomainResult<()> {
+        if self.version == -1 && self.uncommitted_changes.is_empty() {
+            return Err(crate::errors::AppError::AggregateNotFound(command.user_id));
+        }
+        self.rename(command.new_name, clock)
+    }
 }
@@ -0,0 +1,118 @@
+// Clock abstraction - injectable source of "now" for aggregates, envelopes and stores
+use chrono::{DateTime, Duration, Utc};
+use std::sync::{Arc, Mutex};
+
+/// Clock - Provides the current time
+/// Production code uses SystemClock; tests use FixedClock or ManualClock
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Current time as Unix milliseconds (the resolution used by domain events)
+    fn now_millis(&self) -> i64 {
+        self.now().timestamp_millis()
+    }
+}
+
+/// SystemClock - Reads the real wall-clock time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// FixedClock - Always returns the same instant
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock {
+    instant: DateTime<Utc>,
+}
+
+impl FixedClock {
+    pub fn new(instant: DateTime<Utc>) -> Self {
+        FixedClock { instant }
+    }
+
+    pub fn from_millis(millis: i64) -> Self {
+        FixedClock::new(millis_to_datetime(millis))
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.instant
+    }
+}
+
+/// ManualClock - Starts at a given instant and only moves when told to
+/// Clones share the same underlying time, so a test can keep a handle and
+/// advance the clock that was injected into the system under test.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    current: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl ManualClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        ManualClock {
+            current: Arc::new(Mutex::new(start)),
+        }
+    }
+
+    pub fn from_millis(millis: i64) -> Self {
+        ManualClock::new(millis_to_datetime(millis))
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        let mut current = self.current.lock().unwrap();
+        *current += duration;
+    }
+
+    pub fn advance_millis(&self, millis: i64) {
+        self.advance(Duration::milliseconds(millis));
+    }
+
+    pub fn set(&self, instant: DateTime<Utc>) {
+        *self.current.lock().unwrap() = instant;
+    }
+
+    pub fn set_millis(&self, millis: i64) {
+        self.set(millis_to_datetime(millis));
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.current.lock().unwrap()
+    }
+}
+
+fn millis_to_datetime(millis: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(millis).expect("timestamp out of range")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_clock_returns_same_instant() {
+        let clock = FixedClock::from_millis(1_700_000_000_000);
+
+        assert_eq!(clock.now_millis(), 1_700_000_000_000);
+        assert_eq!(clock.now_millis(), 1_700_000_000_000);
+    }
+
+    #[test]
+    fn test_manual_clock_advances_shared_time() {
+        let clock = ManualClock::from_millis(1_000);
+        let handle = clock.clone();
+
+        handle.advance_millis(250);
+        assert_eq!(clock.now_millis(), 1_250);
+
+        handle.set(millis_to_datetime(5_000));
+        assert_eq!(clock.now_millis(), 5_000);
+    }
+}
@@ -1,11 +1,21 @@
 // Domain commands - express intent to change state
 use crate::errors::{AppError, DomainResult};
+use crate::aggregates::{Execute, User};
+
+/// Command - Ties a command type to the aggregate that executes it
+pub trait Command: Sized {
+    type Aggregate: Execute<Self>;
+}
 
 /// RegisterUserCommand - Intent to create a new user
 #[derive(Debug, Clone)]
 pub struct RegisterUserCommand {
     pub user_id: u32,
     pub name: String,
+    /// Client-supplied key that makes retries of this command safe
+    pub idempotency_key: Option<String>,
+    /// Who is issuing the command, recorded on the events it produces
+    pub actor: Option<String>,
 }
 
 impl RegisterUserCommand {
@@ -28,15 +38,38 @@ impl RegisterUserCommand {
             ));
         }
 
-        Ok(RegisterUserCommand { user_id, name })
+        Ok(RegisterUserCommand {
+            user_id,
+            name,
+            idempotency_key: None,
+            actor: None,
+        })
+    }
+
+    pub fn with_idempotency_key(mut self, key: String) -> Self {
+        self.idempotency_key = Some(key);
+        self
+    }
+
+    pub fn with_actor(mut self, actor: String) -> Self {
+        self.actor = Some(actor);
+        self
     }
 }
 
+impl Command for RegisterUserCommand {
+    type Aggregate = User;
+}
+
 /// RenameUserCommand - Intent to rename an existing user
 #[derive(Debug, Clone)]
 pub struct RenameUserCommand {
     pub user_id: u32,
     pub new_name: String,
+    /// Client-supplied key that makes retries of this command safe
+    pub idempotency_key: Option<String>,
+    /// Who is issuing the command, recorded on the events it produces
+    pub actor: Option<String>,
 }
 
 impl RenameUserCommand {
@@ -59,6 +92,99 @@ impl RenameUserCommand {
             ));
         }
 
-        Ok(RenameUserCommand { user_id, new_name })
+        Ok(RenameUserCommand {
+            user_id,
+            new_name,
+            idempotency_key: None,
+            actor: None,
+        })
+    }
+
+    pub fn with_idempotency_key(mut self, key: String) -> Self {
+        self.idempotency_key = Some(key);
+        self
+    }
+
+    pub fn with_actor(mut self, actor: String) -> Self {
+        self.actor = Some(actor);
+        self
+    }
+}
+
+impl Command for RenameUserCommand {
+    type Aggregate = User;
+}
+
+/// UndoLastChangeCommand - Intent to revert the most recent change to a user
+#[derive(Debug, Clone)]
+pub struct UndoLastChangeCommand {
+    pub user_id: u32,
+    /// Why the change is being reverted (recorded on the compensating event)
+    pub reason: Option<String>,
+    /// Who is issuing the command, recorded on the events it produces
+    pub actor: Option<String>,
+}
+
+impl UndoLastChangeCommand {
+    pub fn new(user_id: u32) -> DomainResult<Self> {
+        if user_id == 0 {
+            return Err(AppError::Validation(
+                "User ID must be greater than 0".to_string(),
+            ));
+        }
+
+        Ok(UndoLastChangeCommand {
+            user_id,
+            reason: None,
+            actor: None,
+        })
+    }
+
+    pub fn with_reason(mut self, reason: String) -> Self {
+        self.reason = Some(reason);
+        self
+    }
+
+    pub fn with_actor(mut self, actor: String) -> Self {
+        self.actor = Some(actor);
+        self
+    }
+}
+
+/// MergeUsersCommand - Intent to fold a duplicate account into another
+#[derive(Debug, Clone)]
+pub struct MergeUsersCommand {
+    /// The duplicate account that will redirect to the target
+    pub source_id: u32,
+    /// The account that survives the merge
+    pub target_id: u32,
+    /// Who is issuing the command, recorded on the events it produces
+    pub actor: Option<String>,
+}
+
+impl MergeUsersCommand {
+    pub fn new(source_id: u32, target_id: u32) -> DomainResult<Self> {
+        if source_id == 0 || target_id == 0 {
+            return Err(AppError::Validation(
+                "User ID must be greater than 0".to_string(),
+            ));
+        }
+
+        if source_id == target_id {
+            return Err(AppError::Validation(
+                "Cannot merge a user into itself".to_string(),
+            ));
+        }
+
+        Ok(MergeUsersCommand {
+            source_id,
+            target_id,
+            actor: None,
+        })
+    }
+
+    pub fn with_actor(mut self, actor: String) -> Self {
+        self.actor = Some(actor);
+        self
     }
 }
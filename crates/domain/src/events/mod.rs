@@ -1,5 +1,7 @@
 // Domain events - pure data structures representing facts about what happened
+use std::collections::BTreeMap;
 use std::fmt;
+use crate::clock::{Clock, SystemClock};
 
 /// UserEvent - Enum-based domain events for User aggregate
 #[derive(Debug, Clone, PartialEq)]
@@ -14,6 +16,18 @@ pub enum UserEvent {
         new_name: String,
         timestamp: i64,
     },
+    /// Recorded on the source stream when a duplicate account is merged away
+    MergedInto {
+        user_id: u32,
+        target_id: u32,
+        timestamp: i64,
+    },
+    /// Recorded on the target stream when it absorbs a duplicate account
+    Absorbed {
+        user_id: u32,
+        source_id: u32,
+        timestamp: i64,
+    },
 }
 
 impl UserEvent {
@@ -21,6 +35,8 @@ impl UserEvent {
         match self {
             UserEvent::Registered { user_id, .. } => *user_id,
             UserEvent::Renamed { user_id, .. } => *user_id,
+            UserEvent::MergedInto { user_id, .. } => *user_id,
+            UserEvent::Absorbed { user_id, .. } => *user_id,
         }
     }
 
@@ -28,6 +44,8 @@ impl UserEvent {
         match self {
             UserEvent::Registered { .. } => "UserRegistered",
             UserEvent::Renamed { .. } => "UserRenamed",
+            UserEvent::MergedInto { .. } => "UserMergedInto",
+            UserEvent::Absorbed { .. } => "UserAbsorbed",
         }
     }
 
@@ -35,6 +53,8 @@ impl UserEvent {
         match self {
             UserEvent::Registered { timestamp, .. } => *timestamp,
             UserEvent::Renamed { timestamp, .. } => *timestamp,
+            UserEvent::MergedInto { timestamp, .. } => *timestamp,
+            UserEvent::Absorbed { timestamp, .. } => *timestamp,
         }
     }
 }
@@ -64,12 +84,69 @@ impl fmt::Display for UserEvent {
                     user_id, new_name, timestamp
                 )
             }
+            UserEvent::MergedInto {
+                user_id,
+                target_id,
+                timestamp,
+            } => {
+                write!(
+                    f,
+                    "UserMergedInto(id={}, target_id={}, timestamp={})",
+                    user_id, target_id, timestamp
+                )
+            }
+            UserEvent::Absorbed {
+                user_id,
+                source_id,
+                timestamp,
+            } => {
+                write!(
+                    f,
+                    "UserAbsorbed(id={}, source_id={}, timestamp={})",
+                    user_id, source_id, timestamp
+                )
+            }
+        }
+    }
+}
+
+/// EventMetadata - Command context recorded alongside every stored event
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventMetadata {
+    pub correlation_id: String,
+    pub causation_id: Option<String>,
+    /// Who issued the command (None when the caller is not identified)
+    pub actor: Option<String>,
+    /// Free-form audit details (e.g. reason)
+    pub annotations: BTreeMap<String, String>,
+}
+
+impl EventMetadata {
+    pub fn new(correlation_id: String) -> Self {
+        EventMetadata {
+            correlation_id,
+            ..Default::default()
         }
     }
+
+    pub fn with_causation_id(mut self, causation_id: String) -> Self {
+        self.causation_id = Some(causation_id);
+        self
+    }
+
+    pub fn with_actor(mut self, actor: String) -> Self {
+        self.actor = Some(actor);
+        self
+    }
+
+    pub fn with_annotation(mut self, key: &str, value: String) -> Self {
+        self.annotations.insert(key.to_string(), value);
+        self
+    }
 }
 
 /// EventEnvelope - Wraps events with metadata for distributed tracing
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct EventEnvelope {
     pub aggregate_id: u32,
     pub aggregate_type: String,
@@ -78,6 +155,11 @@ pub struct EventEnvelope {
     pub timestamp: i64,
     pub correlation_id: String,
     pub causation_id: Option<String>,
+    /// Who issued the command that produced the event
+    pub actor: Option<String>,
+    pub annotations: BTreeMap<String, String>,
+    /// Position in the store-wide log across all streams (assigned on append)
+    pub global_position: u64,
 }
 
 impl EventEnvelope {
@@ -86,15 +168,28 @@ impl EventEnvelope {
         event: UserEvent,
         event_version: i32,
         correlation_id: String,
+    ) -> Self {
+        Self::new_with_clock(aggregate_id, event, event_version, correlation_id, &SystemClock)
+    }
+
+    pub fn new_with_clock(
+        aggregate_id: u32,
+        event: UserEvent,
+        event_version: i32,
+        correlation_id: String,
+        clock: &dyn Clock,
     ) -> Self {
         EventEnvelope {
             aggregate_id,
             aggregate_type: "User".to_string(),
             event,
             event_version,
-            timestamp: chrono::Utc::now().timestamp_millis(),
+            timestamp: clock.now_millis(),
             correlation_id,
             causation_id: None,
+            actor: None,
+            annotations: BTreeMap::new(),
+            global_position: 0,
         }
     }
 
@@ -102,6 +197,24 @@ impl EventEnvelope {
         self.causation_id = Some(causation_id);
         self
     }
+
+    /// Copy causation, actor and annotations from command metadata
+    pub fn with_metadata(mut self, metadata: &EventMetadata) -> Self {
+        self.causation_id = metadata.causation_id.clone();
+        self.actor = metadata.actor.clone();
+        self.annotations = metadata.annotations.clone();
+        self
+    }
+
+    /// Stable identifier of the stored event: `<type>-<aggregate id>-<version>`
+    pub fn event_id(&self) -> String {
+        Self::event_id_for(&self.aggregate_type, self.aggregate_id, self.event_version)
+    }
+
+    /// Stable identifier an event will have once stored at `event_version`
+    pub fn event_id_for(aggregate_type: &str, aggregate_id: u32, event_version: i32) -> String {
+        format!("{}-{}-{}", aggregate_type, aggregate_id, event_version)
+    }
 }
 
 #[cfg(test)]
@@ -137,4 +250,39 @@ mod tests {
 
         assert_eq!(envelope.causation_id, Some("cmd_456".to_string()));
     }
+
+    #[test]
+    fn test_event_envelope_id_and_metadata() {
+        let event = UserEvent::Renamed {
+            user_id: 7,
+            new_name: "Bob".to_string(),
+            timestamp: 1000,
+        };
+        let metadata = EventMetadata::new("corr_1".to_string())
+            .with_causation_id("User-7-2".to_string())
+            .with_actor("support:jdoe".to_string())
+            .with_annotation("reason", "typo".to_string());
+
+        let envelope = EventEnvelope::new(7, event, 3, metadata.correlation_id.clone())
+            .with_metadata(&metadata);
+
+        assert_eq!(envelope.event_id(), "User-7-3");
+        assert_eq!(envelope.causation_id, Some("User-7-2".to_string()));
+        assert_eq!(envelope.actor.as_deref(), Some("support:jdoe"));
+        assert_eq!(envelope.annotations.get("reason"), Some(&"typo".to_string()));
+    }
+
+    #[test]
+    fn test_event_envelope_uses_injected_clock() {
+        let event = UserEvent::Registered {
+            user_id: 1,
+            name: "Alice".to_string(),
+            timestamp: 1000,
+        };
+        let clock = crate::clock::FixedClock::from_millis(1_700_000_000_123);
+
+        let envelope = EventEnvelope::new_with_clock(1, event, 0, "corr_123".to_string(), &clock);
+
+        assert_eq!(envelope.timestamp, 1_700_000_000_123);
+    }
 }
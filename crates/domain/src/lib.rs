@@ -4,6 +4,8 @@
 // - Domain Events
 // - Value Objects and Constraints
 // - Repository trait (implementation in persistence crate)
+// - Clock abstraction for injectable timestamps
+// - Test support (Given/When/Then aggregate scenarios)
 // - Errors
 
 pub mod errors;
@@ -11,9 +13,13 @@ pub mod events;
 pub mod aggregates;
 pub mod repository;
 pub mod commands;
+pub mod clock;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 pub use errors::{AppError, DomainError, DomainResult};
 pub use events::UserEvent;
-pub use aggregates::User;
+pub use aggregates::{User, Aggregate, Execute};
 pub use repository::IRepository;
-pub use commands::{RegisterUserCommand, RenameUserCommand};
+pub use commands::{Command, RegisterUserCommand, RenameUserCommand, UndoLastChangeCommand, MergeUsersCommand};
+pub use clock::{Clock, SystemClock, FixedClock, ManualClock};
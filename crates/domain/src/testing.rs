@@ -0,0 +1,168 @@
+// Test support - Given/When/Then scenarios for aggregates
+//
+//     given(vec![registered_event])
+//         .when(RenameUserCommand::new(1, "Bob".to_string())?)
+//         .then_expect(vec![renamed_event]);
+use std::fmt;
+use crate::aggregates::{Aggregate, Execute};
+use crate::clock::FixedClock;
+use crate::commands::Command;
+use crate::errors::{AppError, DomainResult};
+
+/// Instant used to stamp events produced during a scenario
+pub const NOW: i64 = 1_700_000_000_000;
+
+/// Start a scenario from the given event history
+pub fn given<E>(events: Vec<E>) -> Given<E> {
+    Given {
+        history: events,
+        now: NOW,
+    }
+}
+
+/// Given - Prior history of the aggregate under test
+pub struct Given<E> {
+    history: Vec<E>,
+    now: i64,
+}
+
+impl<E> Given<E> {
+    /// Stamp events produced by the command with this instant instead of NOW
+    pub fn at(mut self, millis: i64) -> Self {
+        self.now = millis;
+        self
+    }
+
+    /// Rebuild the aggregate from history and execute the command against it
+    pub fn when<C>(self, command: C) -> Then<C::Aggregate>
+    where
+        C: Command,
+        C::Aggregate: Aggregate<Event = E>,
+    {
+        let clock = FixedClock::from_millis(self.now);
+        let outcome = C::Aggregate::load_from_history(self.history)
+            .and_then(|mut aggregate| {
+                aggregate.execute(command, &clock)?;
+                Ok(aggregate)
+            });
+        Then { outcome }
+    }
+}
+
+/// Then - Outcome of a scenario, checked against expectations
+pub struct Then<A> {
+    outcome: DomainResult<A>,
+}
+
+impl<A: Aggregate> Then<A> {
+    /// Assert the command succeeded and produced exactly these events
+    /// Returns the aggregate for further state assertions.
+    pub fn then_expect(self, expected: Vec<A::Event>) -> A {
+        match self.outcome {
+            Ok(aggregate) => {
+                let actual = aggregate.get_uncommitted_changes();
+                if actual != expected {
+                    panic!("{}", diff_events(&expected, &actual));
+                }
+                aggregate
+            }
+            Err(err) => panic!(
+                "Expected events but command failed:\n  error: {:?}\n  expected: {:#?}",
+                err, expected
+            ),
+        }
+    }
+
+    /// Assert the command failed with exactly this error
+    pub fn then_error(self, expected: AppError) {
+        match self.outcome {
+            Ok(aggregate) => panic!(
+                "Expected error but command succeeded:\n  expected: {:?}\n  produced: {:#?}",
+                expected,
+                aggregate.get_uncommitted_changes()
+            ),
+            Err(actual) => {
+                if actual != expected {
+                    panic!(
+                        "Error mismatch:\n- expected: {:?}\n+ actual:   {:?}",
+                        expected, actual
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Render a line-by-line diff of two event lists
+/// Matching events are prefixed with two spaces, mismatches with -/+.
+pub fn diff_events<E: PartialEq + fmt::Debug>(expected: &[E], actual: &[E]) -> String {
+    let mut out = format!(
+        "Event mismatch (expected {}, got {}):\n",
+        expected.len(),
+        actual.len()
+    );
+    for index in 0..expected.len().max(actual.len()) {
+        match (expected.get(index), actual.get(index)) {
+            (Some(e), Some(a)) if e == a => {
+                out.push_str(&format!("  [{}] {:?}\n", index, e));
+            }
+            (e, a) => {
+                if let Some(e) = e {
+                    out.push_str(&format!("- [{}] {:?}\n", index, e));
+                }
+                if let Some(a) = a {
+                    out.push_str(&format!("+ [{}] {:?}\n", index, a));
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{RegisterUserCommand, RenameUserCommand};
+    use crate::events::UserEvent;
+
+    fn registered(name: &str) -> UserEvent {
+        UserEvent::Registered {
+            user_id: 1,
+            name: name.to_string(),
+            timestamp: NOW,
+        }
+    }
+
+    #[test]
+    fn test_diff_marks_only_mismatched_events() {
+        let diff = diff_events(&[registered("Alice"), registered("Bob")], &[registered("Alice")]);
+
+        assert!(diff.contains("expected 2, got 1"));
+        assert!(diff.contains("  [0]"));
+        assert!(diff.contains("- [1]"));
+        assert!(!diff.contains("+ [1]"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Event mismatch")]
+    fn test_then_expect_panics_on_wrong_events() {
+        given(vec![])
+            .when(RegisterUserCommand::new(1, "Alice".to_string()).unwrap())
+            .then_expect(vec![registered("Bob")]);
+    }
+
+    #[test]
+    fn test_register_rejects_an_existing_user() {
+        given(vec![registered("Alice")])
+            .when(RegisterUserCommand::new(1, "Bob".to_string()).unwrap())
+            .then_error(AppError::Validation("User 1 is already registered".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected error but command succeeded")]
+    fn test_then_error_panics_on_success() {
+        given(vec![registered("Alice")])
+            .when(RenameUserCommand::new(1, "Bob".to_string()).unwrap())
+            .then_error(AppError::AggregateNotFound(1));
+    }
+}
@@ -97,6 +97,12 @@ impl MockLogger {
     }
 }
 
+impl Default for MockLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Logger for MockLogger {
     fn log(&self, level: LogLevel, message: &str) {
         self.messages.lock().unwrap().push((level, message.to_string()));
@@ -1,57 +1,231 @@
 use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
-use domain::events::UserEvent;
+use std::collections::BTreeMap;
+use domain::events::{EventEnvelope, EventMetadata, UserEvent};
+use domain::clock::{Clock, SystemClock};
+use domain::errors::{AppError, DomainResult};
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::UnboundedReceiverStream, Stream, StreamExt};
 
 /// DeadLetterQueueEntry - Record of failed events for inspection and replay
 #[derive(Debug, Clone)]
 pub struct DeadLetterQueueEntry {
+    /// Stable ID for replaying or discarding this entry
+    pub id: u64,
+    /// Handler that failed to process the event, if known
+    pub handler_name: Option<String>,
+    /// Part of the handler that failed, for handlers that feed several
+    /// (e.g. one projection of a registry)
+    pub target: Option<String>,
     pub aggregate_id: u32,
     pub event: UserEvent,
+    /// The stored event as it was published, if it was published with one
+    pub envelope: Option<EventEnvelope>,
+    /// Error from the most recent failure
     pub error_message: String,
     pub failure_count: usize,
     pub last_failed_at: chrono::DateTime<chrono::Utc>,
 }
 
+#[derive(Default)]
+struct DeadLetterQueue {
+    entries: Vec<DeadLetterQueueEntry>,
+    next_id: u64,
+}
+
 /// EventStore - Immutable event log with dead letter queue support
+/// Every event gets a global position in one store-wide log as well as a
+/// version within its own stream. Streams are kept ordered by aggregate ID so
+/// full-log reads are deterministic. Each event is stored in an envelope
+/// carrying both positions, the time it was recorded and the metadata of the
+/// command that produced it.
 pub struct EventStore {
-    events: Arc<Mutex<HashMap<u32, Vec<UserEvent>>>>,
-    dead_letter_queue: Arc<Mutex<Vec<DeadLetterQueueEntry>>>,
+    state: Arc<Mutex<StoreState>>,
+    dead_letter_queue: Arc<Mutex<DeadLetterQueue>>,
+    clock: Arc<dyn Clock>,
+    /// Identifies this log, so derived stores can tell which log built them
+    log_id: Arc<str>,
+}
+
+#[derive(Default)]
+struct StoreState {
+    /// All envelopes in append order; index == global position
+    log: Vec<EventEnvelope>,
+    /// Global positions of each aggregate's events, in stream order
+    streams: BTreeMap<u32, Vec<usize>>,
+    /// Live subscribers, fed on append while the state lock is held
+    subscribers: Vec<mpsc::UnboundedSender<EventEnvelope>>,
+}
+
+impl StoreState {
+    fn stream(&self, aggregate_id: u32) -> impl Iterator<Item = &EventEnvelope> {
+        self.streams
+            .get(&aggregate_id)
+            .into_iter()
+            .flatten()
+            .map(|&position| &self.log[position])
+    }
 }
 
 impl EventStore {
     pub fn new() -> Self {
         EventStore {
-            events: Arc::new(Mutex::new(HashMap::new())),
-            dead_letter_queue: Arc::new(Mutex::new(Vec::new())),
+            state: Arc::new(Mutex::new(StoreState::default())),
+            dead_letter_queue: Arc::new(Mutex::new(DeadLetterQueue::default())),
+            clock: Arc::new(SystemClock),
+            log_id: uuid::Uuid::new_v4().to_string().into(),
         }
     }
 
+    /// Unique ID of this event log; clones share it, a new store gets a new one
+    pub fn log_id(&self) -> &str {
+        &self.log_id
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     pub fn append(&self, aggregate_id: u32, event: UserEvent) {
-        let mut events = self.events.lock().unwrap();
-        events
-            .entry(aggregate_id)
-            .or_insert_with(Vec::new)
-            .push(event);
+        self.append_with_metadata(aggregate_id, event, &EventMetadata::default());
+    }
+
+    /// Append an event and return the stored envelope
+    /// The stream version, global position and recorded-at timestamp are
+    /// assigned by the store. Live subscribers receive the envelope before
+    /// this returns.
+    pub fn append_with_metadata(
+        &self,
+        aggregate_id: u32,
+        event: UserEvent,
+        metadata: &EventMetadata,
+    ) -> EventEnvelope {
+        let mut state = self.state.lock().unwrap();
+        self.append_locked(&mut state, aggregate_id, event, metadata)
+    }
+
+    /// Append events to several streams as one unit
+    ///
+    /// Each entry is `(aggregate ID, expected version, events, metadata)`,
+    /// where the expected version is the stream's last version (-1 for a new
+    /// stream). Every stream is checked first and nothing is appended if any
+    /// has moved on; otherwise all events are appended under one lock, so no
+    /// reader or subscriber sees part of the batch. Returns the stored
+    /// envelopes per entry.
+    pub fn append_batch(
+        &self,
+        batch: Vec<(u32, i32, Vec<UserEvent>, EventMetadata)>,
+    ) -> DomainResult<Vec<Vec<EventEnvelope>>> {
+        let mut state = self.state.lock().unwrap();
+        for (aggregate_id, expected_version, _, _) in &batch {
+            let actual_version = state.streams.get(aggregate_id).map_or(0, Vec::len) as i32 - 1;
+            if actual_version != *expected_version {
+                return Err(AppError::ConcurrencyViolation {
+                    expected_version: *expected_version,
+                    actual_version,
+                });
+            }
+        }
+        Ok(batch
+            .into_iter()
+            .map(|(aggregate_id, _, events, metadata)| {
+                events
+                    .into_iter()
+                    .map(|event| self.append_locked(&mut state, aggregate_id, event, &metadata))
+                    .collect()
+            })
+            .collect())
+    }
+
+    fn append_locked(
+        &self,
+        state: &mut StoreState,
+        aggregate_id: u32,
+        event: UserEvent,
+        metadata: &EventMetadata,
+    ) -> EventEnvelope {
+        let position = state.log.len();
+        let stream = state.streams.entry(aggregate_id).or_default();
+        let mut envelope = EventEnvelope::new_with_clock(
+            aggregate_id,
+            event,
+            stream.len() as i32,
+            metadata.correlation_id.clone(),
+            self.clock.as_ref(),
+        )
+        .with_metadata(metadata);
+        envelope.global_position = position as u64;
+        stream.push(position);
+        state.log.push(envelope.clone());
+        state
+            .subscribers
+            .retain(|subscriber| subscriber.send(envelope.clone()).is_ok());
+        envelope
     }
 
     pub fn get_events(&self, aggregate_id: u32) -> Vec<UserEvent> {
-        let events = self.events.lock().unwrap();
-        events
-            .get(&aggregate_id)
-            .cloned()
-            .unwrap_or_default()
+        self.get_envelopes(aggregate_id)
+            .into_iter()
+            .map(|envelope| envelope.event)
+            .collect()
+    }
+
+    pub fn get_envelopes(&self, aggregate_id: u32) -> Vec<EventEnvelope> {
+        let state = self.state.lock().unwrap();
+        state.stream(aggregate_id).cloned().collect()
     }
 
     pub fn get_all_events(&self) -> Vec<UserEvent> {
-        let events = self.events.lock().unwrap();
-        events
-            .values()
-            .flat_map(|v| v.iter().cloned())
+        let state = self.state.lock().unwrap();
+        state
+            .streams
+            .keys()
+            .flat_map(|&aggregate_id| state.stream(aggregate_id).map(|envelope| envelope.event.clone()))
             .collect()
     }
 
+    /// IDs of every aggregate with at least one stored event, ascending
+    pub fn stream_ids(&self) -> Vec<u32> {
+        self.state.lock().unwrap().streams.keys().copied().collect()
+    }
+
+    /// Envelopes from `position` onwards, in global append order
+    pub fn read_all_from(&self, position: u64) -> Vec<EventEnvelope> {
+        self.read_batch(position, usize::MAX)
+    }
+
+    /// At most `limit` envelopes from `position` onwards, in global append order
+    pub fn read_batch(&self, position: u64, limit: usize) -> Vec<EventEnvelope> {
+        let state = self.state.lock().unwrap();
+        state.log.iter().skip(position as usize).take(limit).cloned().collect()
+    }
+
+    /// Global position the next appended event will get
+    pub fn head_position(&self) -> u64 {
+        self.state.lock().unwrap().log.len() as u64
+    }
+
+    /// Subscribe to every event from `position` onwards
+    ///
+    /// The stream first replays stored history, then continues with live
+    /// appends. History is read and the live feed registered under the same
+    /// lock appends take, so the hand-over has no gaps or duplicates. A
+    /// position beyond the head skips live events until it is reached. Live
+    /// events are buffered without bound; drop the stream to unsubscribe.
+    pub fn subscribe_from(&self, position: u64) -> impl Stream<Item = EventEnvelope> + Send + Unpin + 'static {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let history: Vec<EventEnvelope> = {
+            let mut state = self.state.lock().unwrap();
+            state.subscribers.push(sender);
+            state.log.iter().skip(position as usize).cloned().collect()
+        };
+        let live = UnboundedReceiverStream::new(receiver)
+            .filter(move |envelope| envelope.global_position >= position);
+        tokio_stream::iter(history).chain(live)
+    }
+
     pub fn event_count(&self) -> usize {
-        self.events.lock().unwrap().values().map(|v| v.len()).sum()
+        self.state.lock().unwrap().log.len()
     }
     
     pub fn record_failed_event(
@@ -59,34 +233,92 @@ impl EventStore {
         aggregate_id: u32,
         event: UserEvent,
         error_message: String,
-    ) {
+    ) -> u64 {
+        self.record_dead_letter(None, None, aggregate_id, event, None, error_message)
+    }
+
+    /// Dead-letter a stored event that `handler_name` (or its `target`)
+    /// failed to process
+    /// Repeated failures of the same handler and target on the same event
+    /// update one entry. Returns the entry's ID.
+    pub fn record_failed_delivery(
+        &self,
+        handler_name: &str,
+        target: Option<&str>,
+        envelope: &EventEnvelope,
+        error_message: String,
+    ) -> u64 {
+        self.record_dead_letter(
+            Some(handler_name.to_string()),
+            target.map(str::to_string),
+            envelope.aggregate_id,
+            envelope.event.clone(),
+            Some(envelope.clone()),
+            error_message,
+        )
+    }
+
+    fn record_dead_letter(
+        &self,
+        handler_name: Option<String>,
+        target: Option<String>,
+        aggregate_id: u32,
+        event: UserEvent,
+        envelope: Option<EventEnvelope>,
+        error_message: String,
+    ) -> u64 {
         let mut dlq = self.dead_letter_queue.lock().unwrap();
         
-        if let Some(entry) = dlq.iter_mut().find(|e| e.aggregate_id == aggregate_id && e.event == event) {
+        if let Some(entry) = dlq.entries.iter_mut().find(|e| {
+            e.handler_name == handler_name
+                && e.target == target
+                && e.aggregate_id == aggregate_id
+                && e.event == event
+        }) {
             entry.failure_count += 1;
-            entry.last_failed_at = chrono::Utc::now();
+            entry.error_message = error_message;
+            entry.last_failed_at = self.clock.now();
+            entry.id
         } else {
-            dlq.push(DeadLetterQueueEntry {
+            let id = dlq.next_id;
+            dlq.next_id += 1;
+            dlq.entries.push(DeadLetterQueueEntry {
+                id,
+                handler_name,
+                target,
                 aggregate_id,
                 event,
+                envelope,
                 error_message,
                 failure_count: 1,
-                last_failed_at: chrono::Utc::now(),
+                last_failed_at: self.clock.now(),
             });
+            id
         }
     }
     
     pub fn get_dead_letter_queue(&self) -> Vec<DeadLetterQueueEntry> {
-        self.dead_letter_queue.lock().unwrap().clone()
+        self.dead_letter_queue.lock().unwrap().entries.clone()
+    }
+
+    pub fn get_dlq_entry(&self, id: u64) -> Option<DeadLetterQueueEntry> {
+        self.dead_letter_queue.lock().unwrap().entries.iter().find(|e| e.id == id).cloned()
     }
     
     pub fn remove_from_dlq(&self, aggregate_id: u32, event: &UserEvent) {
         let mut dlq = self.dead_letter_queue.lock().unwrap();
-        dlq.retain(|e| !(e.aggregate_id == aggregate_id && &e.event == event));
+        dlq.entries.retain(|e| !(e.aggregate_id == aggregate_id && &e.event == event));
+    }
+
+    /// Remove one entry by ID; returns it if it was queued
+    pub fn remove_dlq_entry(&self, id: u64) -> Option<DeadLetterQueueEntry> {
+        let mut dlq = self.dead_letter_queue.lock().unwrap();
+        let index = dlq.entries.iter().position(|e| e.id == id)?;
+        Some(dlq.entries.remove(index))
     }
     
     pub fn dlq_size(&self) -> usize {
-        self.dead_letter_queue.lock().unwrap().len()
+        self.dead_letter_queue.lock().unwrap().entries.len()
     }
 }
 
@@ -99,8 +331,89 @@ impl Default for EventStore {
 impl Clone for EventStore {
     fn clone(&self) -> Self {
         EventStore {
-            events: Arc::clone(&self.events),
+            state: Arc::clone(&self.state),
             dead_letter_queue: Arc::clone(&self.dead_letter_queue),
+            clock: Arc::clone(&self.clock),
+            log_id: Arc::clone(&self.log_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::clock::ManualClock;
+
+    #[test]
+    fn test_append_assigns_stream_versions_and_metadata() {
+        let store = EventStore::new();
+        let registered = UserEvent::Registered {
+            user_id: 1,
+            name: "Alice".to_string(),
+            timestamp: 1000,
+        };
+        let renamed = UserEvent::Renamed {
+            user_id: 1,
+            new_name: "Alicia".to_string(),
+            timestamp: 2000,
+        };
+
+        store.append(1, registered);
+        let envelope = store.append_with_metadata(
+            1,
+            renamed,
+            &EventMetadata::new("cmd_1".to_string()).with_causation_id("User-1-0".to_string()),
+        );
+
+        assert_eq!(envelope.event_version, 1);
+        assert_eq!(envelope.event_id(), "User-1-1");
+        assert_eq!(store.get_envelopes(1)[1], envelope);
+        assert_eq!(store.get_envelopes(1)[1].causation_id, Some("User-1-0".to_string()));
+    }
+
+    #[test]
+    fn test_global_positions_span_streams_in_append_order() {
+        let store = EventStore::new();
+        for (id, name) in [(2, "Bob"), (1, "Alice"), (2, "Bobby")] {
+            store.append(id, UserEvent::Registered {
+                user_id: id,
+                name: name.to_string(),
+                timestamp: 1000,
+            });
         }
+
+        let positions: Vec<(u32, i32, u64)> = store
+            .read_all_from(0)
+            .iter()
+            .map(|e| (e.aggregate_id, e.event_version, e.global_position))
+            .collect();
+
+        assert_eq!(positions, vec![(2, 0, 0), (1, 0, 1), (2, 1, 2)]);
+        assert_eq!(store.read_all_from(2).len(), 1);
+        assert_eq!(store.head_position(), 3);
+    }
+
+    #[test]
+    fn test_dlq_timestamps_come_from_injected_clock() {
+        let clock = ManualClock::from_millis(1_700_000_000_000);
+        let store = EventStore::new().with_clock(Arc::new(clock.clone()));
+        let event = UserEvent::Registered {
+            user_id: 1,
+            name: "Alice".to_string(),
+            timestamp: 1000,
+        };
+
+        store.record_failed_event(1, event.clone(), "boom".to_string());
+        assert_eq!(
+            store.get_dead_letter_queue()[0].last_failed_at.timestamp_millis(),
+            1_700_000_000_000
+        );
+
+        clock.advance_millis(5_000);
+        store.record_failed_event(1, event, "boom".to_string());
+
+        let entry = &store.get_dead_letter_queue()[0];
+        assert_eq!(entry.failure_count, 2);
+        assert_eq!(entry.last_failed_at.timestamp_millis(), 1_700_000_005_000);
     }
 }
@@ -0,0 +1,214 @@
+// Audit - Who did what to which user, and when
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use domain::events::EventEnvelope;
+use crate::event_store::EventStore;
+use super::Handles;
+
+/// AuditEntry - One stored event, attributed to the command that caused it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    /// Global position of the event; entries are ordered by it
+    pub position: u64,
+    /// When the event was stored (Unix timestamp in milliseconds)
+    pub timestamp: i64,
+    /// The user whose stream the event was recorded on
+    pub user_id: u32,
+    pub event_type: String,
+    /// Human-readable description of the change
+    pub summary: String,
+    /// Who issued the command (None if the caller was not identified)
+    pub actor: Option<String>,
+    /// Correlation ID of the command, shared by every event it produced
+    pub correlation_id: String,
+    pub causation_id: Option<String>,
+    pub annotations: BTreeMap<String, String>,
+}
+
+impl From<&EventEnvelope> for AuditEntry {
+    fn from(envelope: &EventEnvelope) -> Self {
+        AuditEntry {
+            position: envelope.global_position,
+            timestamp: envelope.timestamp,
+            user_id: envelope.aggregate_id,
+            event_type: envelope.event.event_type().to_string(),
+            summary: envelope.event.to_string(),
+            actor: envelope.actor.clone(),
+            correlation_id: envelope.correlation_id.clone(),
+            causation_id: envelope.causation_id.clone(),
+            annotations: envelope.annotations.clone(),
+        }
+    }
+}
+
+/// AuditQuery - Filters for reading the audit log; every field is optional
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditQuery {
+    pub actor: Option<String>,
+    pub user_id: Option<u32>,
+    /// Inclusive lower bound on the entry timestamp
+    pub from: Option<i64>,
+    /// Exclusive upper bound on the entry timestamp
+    pub to: Option<i64>,
+    /// Only entries after this position (for paging)
+    pub after: Option<u64>,
+    pub limit: usize,
+}
+
+impl Default for AuditQuery {
+    fn default() -> Self {
+        AuditQuery {
+            actor: None,
+            user_id: None,
+            from: None,
+            to: None,
+            after: None,
+            limit: 100,
+        }
+    }
+}
+
+impl AuditQuery {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.actor.as_ref().is_none_or(|actor| entry.actor.as_ref() == Some(actor))
+            && self.user_id.is_none_or(|user_id| entry.user_id == user_id)
+            && self.from.is_none_or(|from| entry.timestamp >= from)
+            && self.to.is_none_or(|to| entry.timestamp < to)
+            && self.after.is_none_or(|after| entry.position > after)
+    }
+}
+
+/// AuditPage - Matching audit entries, oldest first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntry>,
+    /// Pass as `after` to fetch the next page; None on the last page
+    pub next_after: Option<u64>,
+}
+
+/// AuditLogProjection - Queryable audit trail over every stored event
+/// Entries are indexed by actor, target user and time, so each filter
+/// scans only the entries it could match.
+#[derive(Clone, Default)]
+pub struct AuditLogProjection {
+    log: Arc<Mutex<AuditLog>>,
+}
+
+#[derive(Default)]
+struct AuditLog {
+    /// Position -> entry
+    entries: BTreeMap<u64, AuditEntry>,
+    by_actor: HashMap<String, BTreeSet<u64>>,
+    by_user: HashMap<u32, BTreeSet<u64>>,
+    /// (timestamp, position) of every entry, for time range scans
+    by_time: BTreeSet<(i64, u64)>,
+}
+
+impl AuditLog {
+    fn checkpoint(&self) -> Option<u64> {
+        self.entries.keys().next_back().copied()
+    }
+
+    fn insert(&mut self, entry: AuditEntry) {
+        let position = entry.position;
+        if let Some(actor) = &entry.actor {
+            self.by_actor.entry(actor.clone()).or_default().insert(position);
+        }
+        self.by_user.entry(entry.user_id).or_default().insert(position);
+        self.by_time.insert((entry.timestamp, position));
+        self.entries.insert(position, entry);
+    }
+
+    /// Positions that could match, from the narrowest index the query allows
+    fn candidates(&self, query: &AuditQuery) -> Vec<u64> {
+        // An empty (or inverted) window matches nothing, and would panic as a range
+        if let (Some(from), Some(to)) = (query.from, query.to) {
+            if from >= to {
+                return Vec::new();
+            }
+        }
+        let indexed = [
+            query.actor.as_ref().map(|actor| self.by_actor.get(actor)),
+            query.user_id.map(|user_id| self.by_user.get(&user_id)),
+        ];
+        let narrowest = indexed
+            .into_iter()
+            .flatten()
+            .min_by_key(|positions| positions.map_or(0, BTreeSet::len));
+        match narrowest {
+            Some(positions) => positions.into_iter().flatten().copied().collect(),
+            None if query.from.is_some() || query.to.is_some() => {
+                let from = (query.from.unwrap_or(i64::MIN), 0);
+                let to = (query.to.unwrap_or(i64::MAX), 0);
+                let mut positions: Vec<u64> = self.by_time.range(from..to).map(|&(_, position)| position).collect();
+                positions.sort_unstable();
+                positions
+            }
+            None => self.entries.keys().copied().collect(),
+        }
+    }
+}
+
+impl AuditLogProjection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Global position of the last applied envelope (None before any)
+    pub fn checkpoint(&self) -> Option<u64> {
+        self.log.lock().unwrap().checkpoint()
+    }
+
+    /// Record a stored event at most once; returns whether it was recorded
+    pub fn apply(&self, envelope: &EventEnvelope) -> bool {
+        let mut log = self.log.lock().unwrap();
+        if log.checkpoint().is_some_and(|position| envelope.global_position <= position) {
+            return false;
+        }
+        log.insert(AuditEntry::from(envelope));
+        true
+    }
+
+    /// Record every stored event after the checkpoint; returns how many were recorded
+    pub fn catch_up(&self, event_store: &EventStore) -> usize {
+        let from = self.checkpoint().map_or(0, |position| position + 1);
+        event_store
+            .read_all_from(from)
+            .iter()
+            .filter(|envelope| self.apply(envelope))
+            .count()
+    }
+
+    /// Clear the log and rebuild it from the whole event log
+    pub fn rebuild(&self, event_store: &EventStore) -> usize {
+        *self.log.lock().unwrap() = AuditLog::default();
+        self.catch_up(event_store)
+    }
+
+    /// Entries matching every filter in `query`, oldest first
+    pub fn query(&self, query: &AuditQuery) -> AuditPage {
+        let log = self.log.lock().unwrap();
+        let mut entries: Vec<AuditEntry> = log
+            .candidates(query)
+            .into_iter()
+            .map(|position| &log.entries[&position])
+            .filter(|entry| query.matches(entry))
+            .take(query.limit + 1)
+            .cloned()
+            .collect();
+
+        let next_after = if entries.len() > query.limit {
+            entries.truncate(query.limit);
+            entries.last().map(|entry| entry.position)
+        } else {
+            None
+        };
+        AuditPage { entries, next_after }
+    }
+}
+
+impl Handles<EventEnvelope> for AuditLogProjection {
+    fn handle(&self, envelope: &EventEnvelope) {
+        self.apply(envelope);
+    }
+}
@@ -0,0 +1,219 @@
+// Listing - Cursor-paginated, sorted and filtered user queries
+use std::cmp::Ordering;
+use std::fmt;
+use domain::errors::{AppError, DomainResult};
+use super::{NameMatching, UserProjection, UserReadModel};
+
+/// UserSortKey - Field a user listing is ordered by (ties broken by ID)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UserSortKey {
+    #[default]
+    Id,
+    Name,
+    CreatedAt,
+}
+
+impl UserSortKey {
+    fn as_str(self) -> &'static str {
+        match self {
+            UserSortKey::Id => "id",
+            UserSortKey::Name => "name",
+            UserSortKey::CreatedAt => "created_at",
+        }
+    }
+
+    fn compare(self, a: &UserReadModel, b: &UserReadModel) -> Ordering {
+        let by_key = match self {
+            UserSortKey::Id => Ordering::Equal,
+            UserSortKey::Name => a.name.cmp(&b.name),
+            UserSortKey::CreatedAt => a.created_at.cmp(&b.created_at),
+        };
+        by_key.then(a.id.cmp(&b.id))
+    }
+}
+
+impl std::str::FromStr for UserSortKey {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "id" => Ok(UserSortKey::Id),
+            "name" => Ok(UserSortKey::Name),
+            "created_at" => Ok(UserSortKey::CreatedAt),
+            other => Err(AppError::Validation(format!(
+                "Unknown sort key '{}' (expected id, name or created_at)",
+                other
+            ))),
+        }
+    }
+}
+
+/// UserCursor - Position after the last user of a page
+/// Opaque to clients: encoded as hex so it is URL-safe whatever the name holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserCursor {
+    sort: UserSortKey,
+    descending: bool,
+    last: UserReadModel,
+}
+
+impl UserCursor {
+    pub fn encode(&self) -> String {
+        let key = match self.sort {
+            UserSortKey::Id => String::new(),
+            UserSortKey::Name => self.last.name.clone(),
+            UserSortKey::CreatedAt => self.last.created_at.to_string(),
+        };
+        let direction = if self.descending { "desc" } else { "asc" };
+        let raw = format!("{}|{}|{}|{}", self.sort.as_str(), direction, self.last.id, key);
+        raw.bytes().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    pub fn decode(encoded: &str) -> DomainResult<Self> {
+        let invalid = || AppError::Validation("Invalid cursor".to_string());
+        if !encoded.len().is_multiple_of(2) {
+            return Err(invalid());
+        }
+        let bytes = (0..encoded.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(encoded.get(i..i + 2).ok_or_else(invalid)?, 16).map_err(|_| invalid()))
+            .collect::<DomainResult<Vec<u8>>>()?;
+        let raw = String::from_utf8(bytes).map_err(|_| invalid())?;
+
+        let mut parts = raw.splitn(4, '|');
+        let sort: UserSortKey = parts.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+        let descending = match parts.next() {
+            Some("asc") => false,
+            Some("desc") => true,
+            _ => return Err(invalid()),
+        };
+        let id = parts.next().and_then(|id| id.parse().ok()).ok_or_else(invalid)?;
+        let key = parts.next().ok_or_else(invalid)?;
+
+        let mut last = UserReadModel::registered(id, String::new(), 0);
+        match sort {
+            UserSortKey::Id => {}
+            UserSortKey::Name => last.name = key.to_string(),
+            UserSortKey::CreatedAt => last.created_at = key.parse().map_err(|_| invalid())?,
+        }
+        Ok(UserCursor { sort, descending, last })
+    }
+}
+
+impl UserCursor {
+    /// The last user of the page this cursor continues after
+    pub(crate) fn last(&self) -> &UserReadModel {
+        &self.last
+    }
+}
+
+impl fmt::Display for UserCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.encode())
+    }
+}
+
+/// UserListQuery - Sort, filters and page position for listing users
+#[derive(Debug, Clone)]
+pub struct UserListQuery {
+    pub sort: UserSortKey,
+    pub descending: bool,
+    /// Only users whose name starts with this (using the projection's name matching)
+    pub name_prefix: Option<String>,
+    /// Only users created at or after this instant (Unix millis)
+    pub created_from: Option<i64>,
+    /// Only users created before this instant (Unix millis)
+    pub created_to: Option<i64>,
+    /// Continue after the page that produced this cursor
+    pub after: Option<UserCursor>,
+    pub limit: usize,
+}
+
+impl Default for UserListQuery {
+    fn default() -> Self {
+        UserListQuery {
+            sort: UserSortKey::Id,
+            descending: false,
+            name_prefix: None,
+            created_from: None,
+            created_to: None,
+            after: None,
+            limit: 50,
+        }
+    }
+}
+
+/// UserPage - One page of a user listing
+#[derive(Debug, Clone)]
+pub struct UserPage {
+    pub users: Vec<UserReadModel>,
+    /// Cursor for the next page, or None on the last page
+    pub next_cursor: Option<UserCursor>,
+}
+
+impl UserListQuery {
+    /// Filter, sort and cut one page out of `users`
+    /// The cursor must come from a query with the same sort and direction.
+    pub fn page<'a>(
+        &self,
+        users: impl IntoIterator<Item = &'a UserReadModel>,
+        name_matching: NameMatching,
+    ) -> DomainResult<UserPage> {
+        self.check_cursor()?;
+
+        let prefix = self.name_prefix.as_deref().map(|p| name_matching.key(p));
+        let order = |a: &UserReadModel, b: &UserReadModel| {
+            let ordering = self.sort.compare(a, b);
+            if self.descending { ordering.reverse() } else { ordering }
+        };
+
+        let mut users: Vec<UserReadModel> = users
+            .into_iter()
+            .filter(|u| prefix.as_ref().is_none_or(|p| name_matching.key(&u.name).starts_with(p.as_str())))
+            .filter(|u| self.created_from.is_none_or(|from| u.created_at >= from))
+            .filter(|u| self.created_to.is_none_or(|to| u.created_at < to))
+            .filter(|u| self.after.as_ref().is_none_or(|c| order(u, &c.last) == Ordering::Greater))
+            .cloned()
+            .collect();
+        users.sort_by(order);
+
+        Ok(self.finish_page(users))
+    }
+
+    /// Fail if the cursor was issued for a different sort or direction
+    pub(crate) fn check_cursor(&self) -> DomainResult<()> {
+        match &self.after {
+            Some(cursor) if cursor.sort != self.sort || cursor.descending != self.descending => Err(
+                AppError::Validation("Cursor was issued for a different sort order".to_string()),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Cut the page out of matching users already in order; more than
+    /// `limit` of them means there is a next page
+    pub(crate) fn finish_page(&self, mut users: Vec<UserReadModel>) -> UserPage {
+        let has_more = users.len() > self.limit;
+        users.truncate(self.limit);
+        let next_cursor = match users.last() {
+            Some(last) if has_more => Some(UserCursor {
+                sort: self.sort,
+                descending: self.descending,
+                last: last.clone(),
+            }),
+            _ => None,
+        };
+
+        UserPage { users, next_cursor }
+    }
+}
+
+impl UserProjection {
+    /// List users in a stable order, filtered and paged by cursor
+    /// The cursor must come from a query with the same sort and direction.
+    pub fn list_users(&self, query: &UserListQuery) -> DomainResult<UserPage> {
+        let state = self.state();
+        let users = state.users.lock().unwrap();
+        query.page(users.values(), state.name_matching)
+    }
+}
@@ -1,67 +1,555 @@
 // Projections - Read models built from domain events
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::collections::HashMap;
-use domain::events::UserEvent;
+use std::time::Duration;
+use async_trait::async_trait;
+use tokio::sync::Notify;
+use domain::errors::DomainResult;
+use domain::events::{EventEnvelope, UserEvent};
+use crate::event_store::EventStore;
+
+pub mod audit;
+pub mod listing;
+pub mod search;
+pub mod sqlite;
+pub mod stats;
+pub mod verifier;
+
+pub use audit::{AuditEntry, AuditLogProjection, AuditPage, AuditQuery};
+pub use listing::{UserCursor, UserListQuery, UserPage, UserSortKey};
+pub use search::{SearchHit, SearchMode, UserSearchProjection};
+pub use sqlite::SqliteUserProjection;
+pub use stats::{BucketCounts, RenamedUser, StatsBucket, UserStats, UserStatsProjection};
+pub use verifier::{ConsistencyVerifier, Mismatch, VerificationReport};
+
+/// Events replayed between yields during a background rebuild
+pub const REBUILD_BATCH_SIZE: usize = 1_000;
+
+/// UserStatus - Lifecycle state of a user account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserStatus {
+    Active,
+    /// Merged into another account; the live projection drops these, so
+    /// this only shows up when a stream is replayed (e.g. historical reads)
+    Merged,
+}
 
 /// UserReadModel - Denormalized data for queries
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct UserReadModel {
     pub id: u32,
     pub name: String,
     pub created_at: i64,
+    /// Version of the last event applied from the user's stream (0-based),
+    /// usable as the expected version for optimistic concurrency
+    pub version: i32,
+    /// Timestamp of the last event applied from the user's stream
+    pub updated_at: i64,
+    pub rename_count: u32,
+    pub status: UserStatus,
+}
+
+impl UserReadModel {
+    /// Read model for a user that has just registered
+    pub fn registered(user_id: u32, name: String, timestamp: i64) -> Self {
+        UserReadModel {
+            id: user_id,
+            name,
+            created_at: timestamp,
+            version: 0,
+            updated_at: timestamp,
+            rename_count: 0,
+            status: UserStatus::Active,
+        }
+    }
+
+    /// Replay a whole stream; None unless it starts with a registration
+    pub fn from_history(events: &[UserEvent]) -> Option<Self> {
+        let (first, rest) = events.split_first()?;
+        let mut model = match first {
+            UserEvent::Registered { user_id, name, timestamp } => {
+                UserReadModel::registered(*user_id, name.clone(), *timestamp)
+            }
+            _ => return None,
+        };
+        for event in rest {
+            model.record(event);
+        }
+        Some(model)
+    }
+
+    /// Fold a later event from this user's stream into the model
+    pub fn record(&mut self, event: &UserEvent) {
+        self.version += 1;
+        self.updated_at = event.timestamp();
+        match event {
+            UserEvent::Registered { name, .. } => self.name = name.clone(),
+            UserEvent::Renamed { new_name, .. } => {
+                self.name = new_name.clone();
+                self.rename_count += 1;
+            }
+            UserEvent::MergedInto { .. } => self.status = UserStatus::Merged,
+            UserEvent::Absorbed { .. } => {}
+        }
+    }
+}
+
+/// NameMatching - How the name index compares names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NameMatching {
+    /// Names must match exactly
+    #[default]
+    Exact,
+    /// Names are compared after lowercasing, so "alice" finds "Alice"
+    CaseInsensitive,
+}
+
+impl NameMatching {
+    pub(crate) fn key(self, name: &str) -> String {
+        match self {
+            NameMatching::Exact => name.to_string(),
+            NameMatching::CaseInsensitive => name.to_lowercase(),
+        }
+    }
+}
+
+/// RebuildStatus - Where the latest projection rebuild stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RebuildStatus {
+    Idle,
+    Building,
+    Completed,
+    /// Ended without swapping in the new version (e.g. the task panicked)
+    Failed,
+}
+
+/// RebuildProgress - Snapshot of a blue/green rebuild
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebuildProgress {
+    pub status: RebuildStatus,
+    /// Version currently answering queries
+    pub serving_version: u32,
+    /// Version being built (or last built), if any
+    pub target_version: Option<u32>,
+    /// Events replayed into the target version so far
+    pub events_applied: usize,
+    /// Size of the event log when the rebuild started
+    pub events_total: u64,
 }
 
 /// UserProjection - Builds and maintains the read model
+///
+/// The read model is versioned. Queries and live events go to the serving
+/// version; a rebuild fills a fresh version from the event log and then
+/// swaps it in atomically, so readers never see a half-built model. Each
+/// version records the global position of the last stored event it applied,
+/// so it can catch up from the log after a restart.
 pub struct UserProjection {
-    users: Arc<Mutex<HashMap<u32, UserReadModel>>>,
+    current: Arc<RwLock<Arc<ProjectionState>>>,
+    progress: Arc<Mutex<RebuildProgress>>,
+    /// Woken whenever the serving checkpoint advances
+    advanced: Arc<Notify>,
+}
+
+/// RebuildGuard - Claim on the running rebuild
+/// Dropping it before `complete` (a panic or an aborted task) marks the
+/// rebuild failed, so the next one is not refused forever.
+struct RebuildGuard {
+    progress: Arc<Mutex<RebuildProgress>>,
+}
+
+impl RebuildGuard {
+    /// Record that the target version was swapped in
+    fn complete(self, events_applied: usize) {
+        let mut progress = self.progress.lock().unwrap_or_else(PoisonError::into_inner);
+        progress.events_applied = events_applied;
+        if let Some(target_version) = progress.target_version {
+            progress.serving_version = target_version;
+        }
+        progress.status = RebuildStatus::Completed;
+    }
+}
+
+impl Drop for RebuildGuard {
+    fn drop(&mut self) {
+        let mut progress = self.progress.lock().unwrap_or_else(PoisonError::into_inner);
+        if progress.status == RebuildStatus::Building {
+            progress.status = RebuildStatus::Failed;
+        }
+    }
+}
+
+/// One version of the read model
+struct ProjectionState {
+    version: u32,
+    users: Mutex<HashMap<u32, UserReadModel>>,
+    /// Secondary index: name key -> user ID; always locked after `users`
+    names: Mutex<HashMap<String, u32>>,
+    name_matching: NameMatching,
+    /// Merged-away user ID -> ID it was merged into
+    redirects: Mutex<HashMap<u32, u32>>,
+    /// Global position of the last applied envelope; held while applying so
+    /// live delivery, replays and promotion are serialized
+    checkpoint: Mutex<Option<u64>>,
+    /// Set once a newer version has been promoted in this one's place
+    retired: AtomicBool,
 }
 
 impl UserProjection {
     pub fn new() -> Self {
         UserProjection {
-            users: Arc::new(Mutex::new(HashMap::new())),
+            current: Arc::new(RwLock::new(Arc::new(ProjectionState::new(1, NameMatching::Exact)))),
+            progress: Arc::new(Mutex::new(RebuildProgress {
+                status: RebuildStatus::Idle,
+                serving_version: 1,
+                target_version: None,
+                events_applied: 0,
+                events_total: 0,
+            })),
+            advanced: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Choose how names are matched; call before any events are applied
+    pub fn with_name_matching(self, name_matching: NameMatching) -> Self {
+        let version = self.version();
+        *self.current.write().unwrap() = Arc::new(ProjectionState::new(version, name_matching));
+        self
+    }
+
+    fn state(&self) -> Arc<ProjectionState> {
+        Arc::clone(&self.current.read().unwrap())
+    }
+
+    /// Version of the read model currently answering queries
+    pub fn version(&self) -> u32 {
+        self.state().version
+    }
+
+    /// Global position of the last applied envelope (None before any)
+    pub fn checkpoint(&self) -> Option<u64> {
+        *self.state().checkpoint.lock().unwrap()
+    }
+
+    /// Apply a stored event at most once
+    /// Envelopes at or before the checkpoint were already applied and are
+    /// skipped. Returns whether the envelope changed the read model.
+    pub fn apply(&self, envelope: &EventEnvelope) -> bool {
+        loop {
+            let state = self.state();
+            let mut checkpoint = state.checkpoint.lock().unwrap();
+            // Promoted away while we waited: deliver to the new version instead
+            if state.retired.load(Ordering::SeqCst) {
+                continue;
+            }
+            let applied = state.apply_locked(&mut checkpoint, envelope);
+            drop(checkpoint);
+            if applied {
+                self.advanced.notify_waiters();
+            }
+            return applied;
+        }
+    }
+
+    /// Apply every stored event after the checkpoint; returns how many were applied
+    pub fn catch_up(&self, event_store: &EventStore) -> usize {
+        let applied = self.state().catch_up(event_store, usize::MAX);
+        if applied > 0 {
+            self.advanced.notify_waiters();
+        }
+        applied
+    }
+
+    /// Whether the serving version has applied the event at `position`
+    pub fn has_applied(&self, position: u64) -> bool {
+        self.checkpoint().is_some_and(|checkpoint| checkpoint >= position)
+    }
+
+    /// Wait until the event at `position` has been applied, for at most `timeout`
+    /// Returns whether it was. Lets a client read its own write once events
+    /// reach the projection asynchronously.
+    pub async fn wait_for_position(&self, position: u64, timeout: Duration) -> bool {
+        wait_until(&self.advanced, timeout, || self.has_applied(position)).await
+    }
+
+    /// Build a new version from the whole event log and swap it in
+    /// Queries keep hitting the old version until the swap. Returns the
+    /// number of events replayed, or None if a rebuild is already running.
+    pub fn rebuild(&self, event_store: &EventStore) -> Option<usize> {
+        let guard = self.begin_rebuild(event_store)?;
+        let next = Arc::new(self.state().successor());
+        let applied = next.catch_up(event_store, usize::MAX);
+        let applied = applied + self.promote(next, event_store);
+        guard.complete(applied);
+        Some(applied)
+    }
+
+    /// Start a blue/green rebuild in the background
+    /// Returns None if a rebuild is already running. Progress is reported by
+    /// `rebuild_progress`.
+    pub fn start_rebuild(&self, event_store: &EventStore) -> Option<tokio::task::JoinHandle<()>> {
+        let guard = self.begin_rebuild(event_store)?;
+        let projection = self.clone();
+        let event_store = event_store.clone();
+        Some(tokio::spawn(async move {
+            projection.run_rebuild(guard, &event_store).await;
+        }))
+    }
+
+    pub fn rebuild_progress(&self) -> RebuildProgress {
+        self.progress.lock().unwrap().clone()
+    }
+
+    /// Mark a rebuild as running; None if one already is
+    fn begin_rebuild(&self, event_store: &EventStore) -> Option<RebuildGuard> {
+        let mut progress = self.progress.lock().unwrap();
+        if progress.status == RebuildStatus::Building {
+            return None;
         }
+        let serving_version = self.version();
+        *progress = RebuildProgress {
+            status: RebuildStatus::Building,
+            serving_version,
+            target_version: Some(serving_version + 1),
+            events_applied: 0,
+            events_total: event_store.head_position(),
+        };
+        Some(RebuildGuard {
+            progress: Arc::clone(&self.progress),
+        })
     }
 
+    async fn run_rebuild(&self, guard: RebuildGuard, event_store: &EventStore) {
+        let next = Arc::new(self.state().successor());
+        let mut total = 0;
+        loop {
+            let applied = next.catch_up(event_store, REBUILD_BATCH_SIZE);
+            total += applied;
+            self.progress.lock().unwrap().events_applied = total;
+            if applied < REBUILD_BATCH_SIZE {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+
+        total += self.promote(next, event_store);
+        guard.complete(total);
+    }
+
+    /// Swap `next` in as the serving version once it has caught up
+    /// Live deliveries to the old version are held off while `next` applies
+    /// the remaining tail of the log, so nothing is lost in the switch.
+    fn promote(&self, next: Arc<ProjectionState>, event_store: &EventStore) -> usize {
+        let mut current = self.current.write().unwrap();
+        let old = Arc::clone(&current);
+        let _held = old.checkpoint.lock().unwrap();
+        let applied = next.catch_up(event_store, usize::MAX);
+        *current = next;
+        old.retired.store(true, Ordering::SeqCst);
+        self.advanced.notify_waiters();
+        applied
+    }
+
+    /// Update the read model for one event, without checkpointing
+    pub fn apply_event(&self, event: &UserEvent) {
+        self.state().apply_event(event);
+    }
+
+    /// Look up a user, following merge redirects to the surviving account
     pub fn get_user(&self, user_id: u32) -> Option<UserReadModel> {
-        self.users.lock().unwrap().get(&user_id).cloned()
+        let state = self.state();
+        let user_id = state.resolve_id(user_id);
+        let user = state.users.lock().unwrap().get(&user_id).cloned();
+        user
+    }
+
+    /// The ID a lookup for `user_id` ends up at after following merges
+    pub fn resolve_id(&self, user_id: u32) -> u32 {
+        self.state().resolve_id(user_id)
     }
 
+    /// All users, ordered by ID so callers see a stable sequence
     pub fn get_all_users(&self) -> Vec<UserReadModel> {
-        self.users
+        let state = self.state();
+        let mut users: Vec<UserReadModel> = state.users
             .lock()
             .unwrap()
             .values()
             .cloned()
-            .collect()
+            .collect();
+        users.sort_by_key(|u| u.id);
+        users
     }
 
+    /// Look up a user by name through the name index
     pub fn find_by_name(&self, name: &str) -> Option<UserReadModel> {
-        // NOTE: This performs an O(n) linear scan. For production systems with 
-        // large user bases, consider adding a secondary index (HashMap<String, u32>)
-        // mapping names to user IDs for O(1) lookups.
-        self.users
-            .lock()
-            .unwrap()
-            .values()
-            .find(|u| u.name == name)
-            .cloned()
+        let state = self.state();
+        let key = state.name_matching.key(name);
+        let user_id = state.names.lock().unwrap().get(&key).copied()?;
+        let user = state.users.lock().unwrap().get(&user_id).cloned();
+        user
     }
 
-    fn handle_user_registered(&self, user_id: u32, name: String, timestamp: i64) {
-        let user = UserReadModel {
-            id: user_id,
-            name,
-            created_at: timestamp,
+    /// The stored entry for exactly this ID, without following redirects
+    pub(crate) fn entry(&self, user_id: u32) -> Option<UserReadModel> {
+        let state = self.state();
+        let user = state.users.lock().unwrap().get(&user_id).cloned();
+        user
+    }
+
+    /// Where this ID redirects after a merge, if anywhere
+    pub(crate) fn redirect(&self, user_id: u32) -> Option<u32> {
+        let state = self.state();
+        let target = state.redirects.lock().unwrap().get(&user_id).copied();
+        target
+    }
+
+    /// Overwrite one user's entry and redirect with known-good values
+    /// Serialized with live deliveries; the checkpoint is left untouched.
+    pub(crate) fn repair_user(&self, user_id: u32, entry: Option<UserReadModel>, redirect: Option<u32>) {
+        let state = self.state();
+        let _held = state.checkpoint.lock().unwrap();
+        {
+            let mut users = state.users.lock().unwrap();
+            let mut names = state.names.lock().unwrap();
+            if let Some(previous) = users.remove(&user_id) {
+                state.unindex(&mut names, &previous.name, user_id);
+            }
+            if let Some(entry) = entry {
+                names.insert(state.name_matching.key(&entry.name), user_id);
+                users.insert(user_id, entry);
+            }
+        }
+        let mut redirects = state.redirects.lock().unwrap();
+        match redirect {
+            Some(target_id) => redirects.insert(user_id, target_id),
+            None => redirects.remove(&user_id),
         };
-        self.users.lock().unwrap().insert(user_id, user);
+    }
+}
+
+impl ProjectionState {
+    fn new(version: u32, name_matching: NameMatching) -> Self {
+        ProjectionState {
+            version,
+            users: Mutex::new(HashMap::new()),
+            names: Mutex::new(HashMap::new()),
+            name_matching,
+            redirects: Mutex::new(HashMap::new()),
+            checkpoint: Mutex::new(None),
+            retired: AtomicBool::new(false),
+        }
+    }
+
+    /// Empty state for the next version, with the same configuration
+    fn successor(&self) -> ProjectionState {
+        ProjectionState::new(self.version + 1, self.name_matching)
+    }
+
+    /// Drop `name` from the index if it still points at `user_id`
+    fn unindex(&self, names: &mut HashMap<String, u32>, name: &str, user_id: u32) {
+        let key = self.name_matching.key(name);
+        if names.get(&key) == Some(&user_id) {
+            names.remove(&key);
+        }
+    }
+
+    /// Apply up to `limit` stored events after the checkpoint
+    fn catch_up(&self, event_store: &EventStore, limit: usize) -> usize {
+        let mut checkpoint = self.checkpoint.lock().unwrap();
+        let from = checkpoint.map_or(0, |position| position + 1);
+        event_store
+            .read_batch(from, limit)
+            .iter()
+            .filter(|envelope| self.apply_locked(&mut checkpoint, envelope))
+            .count()
+    }
+
+    fn apply_locked(&self, checkpoint: &mut Option<u64>, envelope: &EventEnvelope) -> bool {
+        if checkpoint.is_some_and(|position| envelope.global_position <= position) {
+            return false;
+        }
+        self.apply_event(&envelope.event);
+        *checkpoint = Some(envelope.global_position);
+        true
+    }
+
+    fn apply_event(&self, event: &UserEvent) {
+        match event {
+            UserEvent::Registered { user_id, name, .. } => {
+                self.handle_user_registered(*user_id, name, event)
+            }
+            UserEvent::Renamed { user_id, new_name, .. } => {
+                self.handle_user_renamed(*user_id, new_name, event)
+            }
+            UserEvent::MergedInto {
+                user_id,
+                target_id,
+                ..
+            } => self.handle_user_merged(*user_id, *target_id),
+            UserEvent::Absorbed { user_id, .. } => {
+                if let Some(user) = self.users.lock().unwrap().get_mut(user_id) {
+                    user.record(event);
+                }
+            }
+        }
+    }
+
+    fn resolve_id(&self, user_id: u32) -> u32 {
+        let redirects = self.redirects.lock().unwrap();
+        let mut current = user_id;
+        // Bounded walk guards against a corrupt redirect cycle
+        for _ in 0..=redirects.len() {
+            match redirects.get(&current) {
+                Some(&target) => current = target,
+                None => break,
+            }
+        }
+        current
+    }
+
+    /// A registration on a stream we already track continues its history
+    /// (version, created_at, rename count) rather than starting over
+    fn handle_user_registered(&self, user_id: u32, name: &str, event: &UserEvent) {
+        let key = self.name_matching.key(name);
+        {
+            let mut users = self.users.lock().unwrap();
+            let mut names = self.names.lock().unwrap();
+            let user = match users.remove(&user_id) {
+                Some(mut previous) => {
+                    self.unindex(&mut names, &previous.name, user_id);
+                    previous.record(event);
+                    previous
+                }
+                None => UserReadModel::registered(user_id, name.to_string(), event.timestamp()),
+            };
+            users.insert(user_id, user);
+            names.insert(key, user_id);
+        }
+        self.redirects.lock().unwrap().remove(&user_id);
     }
 
-    fn handle_user_renamed(&self, user_id: u32, new_name: String, _timestamp: i64) {
+    fn handle_user_renamed(&self, user_id: u32, new_name: &str, event: &UserEvent) {
         let mut users = self.users.lock().unwrap();
         if let Some(user) = users.get_mut(&user_id) {
-            user.name = new_name;
+            let mut names = self.names.lock().unwrap();
+            self.unindex(&mut names, &user.name, user_id);
+            names.insert(self.name_matching.key(new_name), user_id);
+            user.record(event);
+        }
+    }
+
+    /// The source disappears from listings and name lookups (releasing its
+    /// name) and lookups of its ID redirect to the target
+    fn handle_user_merged(&self, user_id: u32, target_id: u32) {
+        {
+            let mut users = self.users.lock().unwrap();
+            if let Some(previous) = users.remove(&user_id) {
+                self.unindex(&mut self.names.lock().unwrap(), &previous.name, user_id);
+            }
         }
+        self.redirects.lock().unwrap().insert(user_id, target_id);
     }
 }
 
@@ -74,11 +562,84 @@ impl Default for UserProjection {
 impl Clone for UserProjection {
     fn clone(&self) -> Self {
         UserProjection {
-            users: Arc::clone(&self.users),
+            current: Arc::clone(&self.current),
+            progress: Arc::clone(&self.progress),
+            advanced: Arc::clone(&self.advanced),
         }
     }
 }
 
+/// Wait for `done` to hold, re-checking each time `advanced` fires
+async fn wait_until(advanced: &Notify, timeout: Duration, done: impl Fn() -> bool) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        // Register before checking so an advance in between is not missed
+        let notified = advanced.notified();
+        if done() {
+            return true;
+        }
+        if tokio::time::timeout_at(deadline, notified).await.is_err() {
+            return done();
+        }
+    }
+}
+
+/// UserQueries - Query surface shared by the user read models
+/// Lets the API answer reads from whichever store is configured; queries are
+/// async so stores backed by disk can run them off the runtime threads.
+#[async_trait]
+pub trait UserQueries: Send + Sync {
+    /// Look up a user, following merge redirects to the surviving account
+    async fn get_user(&self, user_id: u32) -> DomainResult<Option<UserReadModel>>;
+
+    /// The ID a lookup for `user_id` ends up at after following merges
+    async fn resolve_id(&self, user_id: u32) -> DomainResult<u32>;
+
+    /// All users, ordered by ID
+    async fn get_all_users(&self) -> DomainResult<Vec<UserReadModel>>;
+
+    async fn find_by_name(&self, name: &str) -> DomainResult<Option<UserReadModel>>;
+
+    async fn list_users(&self, query: &UserListQuery) -> DomainResult<UserPage>;
+
+    /// Global position of the last applied event (None before any)
+    async fn checkpoint(&self) -> DomainResult<Option<u64>>;
+
+    /// Wait until the event at `position` has been applied, for at most `timeout`
+    async fn wait_for_position(&self, position: u64, timeout: Duration) -> bool;
+}
+
+#[async_trait]
+impl UserQueries for UserProjection {
+    async fn get_user(&self, user_id: u32) -> DomainResult<Option<UserReadModel>> {
+        Ok(UserProjection::get_user(self, user_id))
+    }
+
+    async fn resolve_id(&self, user_id: u32) -> DomainResult<u32> {
+        Ok(UserProjection::resolve_id(self, user_id))
+    }
+
+    async fn get_all_users(&self) -> DomainResult<Vec<UserReadModel>> {
+        Ok(UserProjection::get_all_users(self))
+    }
+
+    async fn find_by_name(&self, name: &str) -> DomainResult<Option<UserReadModel>> {
+        Ok(UserProjection::find_by_name(self, name))
+    }
+
+    async fn list_users(&self, query: &UserListQuery) -> DomainResult<UserPage> {
+        UserProjection::list_users(self, query)
+    }
+
+    async fn checkpoint(&self) -> DomainResult<Option<u64>> {
+        Ok(UserProjection::checkpoint(self))
+    }
+
+    async fn wait_for_position(&self, position: u64, timeout: Duration) -> bool {
+        UserProjection::wait_for_position(self, position, timeout).await
+    }
+}
+
 /// Handles<T> - Strongly-typed event handlers
 pub trait Handles<T> {
     fn handle(&self, event: &T);
@@ -101,23 +662,12 @@ impl TypedUserProjectionHandler {
 
 impl Handles<UserEvent> for TypedUserProjectionHandler {
     fn handle(&self, event: &UserEvent) {
-        match event {
-            UserEvent::Registered {
-                user_id,
-                name,
-                timestamp,
-            } => {
-                self.projection
-                    .handle_user_registered(*user_id, name.clone(), *timestamp);
-            }
-            UserEvent::Renamed {
-                user_id,
-                new_name,
-                timestamp,
-            } => {
-                self.projection
-                    .handle_user_renamed(*user_id, new_name.clone(), *timestamp);
-            }
-        }
+        self.projection.apply_event(event);
+    }
+}
+
+impl Handles<EventEnvelope> for TypedUserProjectionHandler {
+    fn handle(&self, envelope: &EventEnvelope) {
+        self.projection.apply(envelope);
     }
 }
@@ -0,0 +1,254 @@
+// Search - Prefix, substring and typo-tolerant user search
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use domain::errors::AppError;
+use domain::events::{EventEnvelope, UserEvent};
+use crate::event_store::EventStore;
+use super::Handles;
+
+/// SearchMode - How a query is matched against user names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchMode {
+    /// Names starting with the query
+    #[default]
+    Prefix,
+    /// Names containing the query anywhere
+    Substring,
+    /// Names within a small edit distance of the query
+    Fuzzy,
+}
+
+impl std::str::FromStr for SearchMode {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "prefix" => Ok(SearchMode::Prefix),
+            "substring" => Ok(SearchMode::Substring),
+            "fuzzy" => Ok(SearchMode::Fuzzy),
+            other => Err(AppError::Validation(format!(
+                "Unknown search mode '{}' (expected prefix, substring or fuzzy)",
+                other
+            ))),
+        }
+    }
+}
+
+/// SearchHit - One ranked search result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    pub user_id: u32,
+    pub name: String,
+    /// Lower is better: edit distance for fuzzy matches, otherwise the number
+    /// of characters the name has beyond the query
+    pub score: usize,
+}
+
+/// Largest edit distance a fuzzy match may have for a query of this length
+pub fn max_typos(query_len: usize) -> usize {
+    match query_len {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+/// UserSearchProjection - Case-insensitive search index over user names
+/// Maintained incrementally from UserEvents; merged-away users drop out.
+#[derive(Clone, Default)]
+pub struct UserSearchProjection {
+    index: Arc<Mutex<SearchIndex>>,
+}
+
+#[derive(Default)]
+struct SearchIndex {
+    /// Lowercased name -> users with that name, ordered for prefix range scans
+    by_key: BTreeMap<String, BTreeSet<u32>>,
+    /// User ID -> current display name
+    names: HashMap<u32, String>,
+    /// Global position of the last applied envelope
+    checkpoint: Option<u64>,
+}
+
+impl SearchIndex {
+    fn insert(&mut self, user_id: u32, name: &str) {
+        self.remove(user_id);
+        self.by_key.entry(name.to_lowercase()).or_default().insert(user_id);
+        self.names.insert(user_id, name.to_string());
+    }
+
+    fn remove(&mut self, user_id: u32) {
+        if let Some(previous) = self.names.remove(&user_id) {
+            let key = previous.to_lowercase();
+            if let Some(ids) = self.by_key.get_mut(&key) {
+                ids.remove(&user_id);
+                if ids.is_empty() {
+                    self.by_key.remove(&key);
+                }
+            }
+        }
+    }
+
+    fn apply_event(&mut self, event: &UserEvent) {
+        match event {
+            UserEvent::Registered { user_id, name, .. } => self.insert(*user_id, name),
+            UserEvent::Renamed { user_id, new_name, .. } => self.insert(*user_id, new_name),
+            UserEvent::MergedInto { user_id, .. } => self.remove(*user_id),
+            UserEvent::Absorbed { .. } => {}
+        }
+    }
+
+    fn hits<'a>(&'a self, ids: &'a BTreeSet<u32>, score: usize) -> impl Iterator<Item = SearchHit> + 'a {
+        ids.iter().map(move |&user_id| SearchHit {
+            user_id,
+            name: self.names[&user_id].clone(),
+            score,
+        })
+    }
+}
+
+impl UserSearchProjection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Global position of the last applied envelope (None before any)
+    pub fn checkpoint(&self) -> Option<u64> {
+        self.index.lock().unwrap().checkpoint
+    }
+
+    /// Apply a stored event at most once; returns whether it was applied
+    pub fn apply(&self, envelope: &EventEnvelope) -> bool {
+        let mut index = self.index.lock().unwrap();
+        if index.checkpoint.is_some_and(|position| envelope.global_position <= position) {
+            return false;
+        }
+        index.apply_event(&envelope.event);
+        index.checkpoint = Some(envelope.global_position);
+        true
+    }
+
+    /// Apply every stored event after the checkpoint; returns how many were applied
+    pub fn catch_up(&self, event_store: &EventStore) -> usize {
+        let from = self.checkpoint().map_or(0, |position| position + 1);
+        event_store
+            .read_all_from(from)
+            .iter()
+            .filter(|envelope| self.apply(envelope))
+            .count()
+    }
+
+    /// Clear the index and rebuild it from the whole event log
+    pub fn rebuild(&self, event_store: &EventStore) -> usize {
+        *self.index.lock().unwrap() = SearchIndex::default();
+        self.catch_up(event_store)
+    }
+
+    /// Update the index for one event, without checkpointing
+    pub fn apply_event(&self, event: &UserEvent) {
+        self.index.lock().unwrap().apply_event(event);
+    }
+
+    /// Ranked matches for `query`, best first, at most `limit` of them
+    /// Matching ignores case. Ties are broken by name, then user ID.
+    pub fn search(&self, query: &str, mode: SearchMode, limit: usize) -> Vec<SearchHit> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let query_len = query.chars().count();
+        let index = self.index.lock().unwrap();
+
+        let mut hits: Vec<SearchHit> = match mode {
+            SearchMode::Prefix => index
+                .by_key
+                .range(query.clone()..)
+                .take_while(|(key, _)| key.starts_with(&query))
+                .flat_map(|(key, ids)| index.hits(ids, key.chars().count() - query_len))
+                .collect(),
+            SearchMode::Substring => index
+                .by_key
+                .iter()
+                .filter(|(key, _)| key.contains(&query))
+                .flat_map(|(key, ids)| index.hits(ids, key.chars().count() - query_len))
+                .collect(),
+            SearchMode::Fuzzy => {
+                let max = max_typos(query_len);
+                index
+                    .by_key
+                    .iter()
+                    .filter_map(|(key, ids)| {
+                        bounded_edit_distance(&query, key, max).map(|distance| (ids, distance))
+                    })
+                    .flat_map(|(ids, distance)| index.hits(ids, distance))
+                    .collect()
+            }
+        };
+
+        hits.sort_by(|a, b| {
+            a.score
+                .cmp(&b.score)
+                .then_with(|| a.name.cmp(&b.name))
+                .then(a.user_id.cmp(&b.user_id))
+        });
+        hits.truncate(limit);
+        hits
+    }
+}
+
+impl Handles<UserEvent> for UserSearchProjection {
+    fn handle(&self, event: &UserEvent) {
+        self.apply_event(event);
+    }
+}
+
+impl Handles<EventEnvelope> for UserSearchProjection {
+    fn handle(&self, envelope: &EventEnvelope) {
+        self.apply(envelope);
+    }
+}
+
+/// Edit distance between `a` and `b`, or None if it exceeds `max`
+/// Counts insertions, deletions, substitutions and swaps of adjacent
+/// characters (optimal string alignment), so "alcie" is one typo from "alice".
+pub fn bounded_edit_distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+
+    let mut before_previous: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+            if i > 0 && j > 0 && *ca == b[j - 1] && a[i - 1] == *cb {
+                current[j + 1] = current[j + 1].min(before_previous[j - 1] + 1);
+            }
+        }
+        // Every path through this row already costs more than allowed
+        if current.iter().min().is_some_and(|&best| best > max) {
+            return None;
+        }
+        before_previous = std::mem::replace(&mut previous, current);
+    }
+
+    Some(previous[b.len()]).filter(|&distance| distance <= max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounded_edit_distance() {
+        assert_eq!(bounded_edit_distance("alice", "alice", 2), Some(0));
+        assert_eq!(bounded_edit_distance("alcie", "alice", 1), Some(1));
+        assert_eq!(bounded_edit_distance("alise", "alice", 1), Some(1));
+        assert_eq!(bounded_edit_distance("bob", "alice", 2), None);
+        assert_eq!(bounded_edit_distance("ali", "alice", 1), None);
+    }
+}
@@ -0,0 +1,515 @@
+// SQLite read model - UserProjection persisted to disk
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use async_trait::async_trait;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row, Transaction};
+use tokio::sync::Notify;
+use domain::errors::{AppError, DomainResult};
+use domain::events::{EventEnvelope, UserEvent};
+use crate::event_store::EventStore;
+use super::{
+    wait_until, NameMatching, UserListQuery, UserPage, UserQueries, UserReadModel, UserSortKey,
+    UserStatus, REBUILD_BATCH_SIZE,
+};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS users (
+        id           INTEGER PRIMARY KEY,
+        name         TEXT    NOT NULL,
+        name_key     TEXT    NOT NULL,
+        created_at   INTEGER NOT NULL,
+        version      INTEGER NOT NULL,
+        updated_at   INTEGER NOT NULL,
+        rename_count INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS users_by_name_key ON users (name_key);
+    CREATE TABLE IF NOT EXISTS redirects (
+        user_id   INTEGER PRIMARY KEY,
+        target_id INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS checkpoint (
+        id       INTEGER PRIMARY KEY CHECK (id = 0),
+        position INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS source_log (
+        id     INTEGER PRIMARY KEY CHECK (id = 0),
+        log_id TEXT    NOT NULL
+    );
+";
+
+const USER_COLUMNS: &str = "id, name, created_at, version, updated_at, rename_count";
+
+fn db_error(err: rusqlite::Error) -> AppError {
+    AppError::RepositoryError(format!("SQLite read model: {}", err))
+}
+
+fn sort_column(sort: UserSortKey) -> &'static str {
+    match sort {
+        UserSortKey::Id => "id",
+        UserSortKey::Name => "name",
+        UserSortKey::CreatedAt => "created_at",
+    }
+}
+
+/// Rows only exist for live accounts; merged users become redirects
+fn user_from_row(row: &Row<'_>) -> rusqlite::Result<UserReadModel> {
+    Ok(UserReadModel {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        created_at: row.get(2)?,
+        version: row.get(3)?,
+        updated_at: row.get(4)?,
+        rename_count: row.get(5)?,
+        status: UserStatus::Active,
+    })
+}
+
+/// SqliteUserProjection - The user read model kept in a SQLite database
+///
+/// Answers the same queries as `UserProjection` from a database file that
+/// reporting tools can read. Each batch of events is applied in one
+/// transaction together with the checkpoint, so the stored position always
+/// matches the stored rows. The database also records which event log it
+/// was built from: `attach` keeps the rows when reopened against that same
+/// log and rebuilds them otherwise. With the in-memory event store every
+/// process starts a new log, so in practice the rows are rebuilt on boot.
+pub struct SqliteUserProjection {
+    conn: Arc<Mutex<Connection>>,
+    name_matching: NameMatching,
+    /// Copy of the stored checkpoint, so waiting readers need no queries
+    applied: Arc<Mutex<Option<u64>>>,
+    /// Woken whenever the checkpoint advances
+    advanced: Arc<Notify>,
+}
+
+impl SqliteUserProjection {
+    /// Open (or create) the database at `path`
+    pub fn open(path: impl AsRef<Path>) -> DomainResult<Self> {
+        let conn = Connection::open(path).map_err(db_error)?;
+        // Lets reporting tools read while events are being applied
+        conn.pragma_update(None, "journal_mode", "WAL").map_err(db_error)?;
+        Self::from_connection(conn)
+    }
+
+    /// A private database that lives as long as this projection
+    pub fn open_in_memory() -> DomainResult<Self> {
+        Self::from_connection(Connection::open_in_memory().map_err(db_error)?)
+    }
+
+    fn from_connection(conn: Connection) -> DomainResult<Self> {
+        conn.execute_batch(SCHEMA).map_err(db_error)?;
+        let checkpoint = read_checkpoint(&conn).map_err(db_error)?;
+        Ok(SqliteUserProjection {
+            conn: Arc::new(Mutex::new(conn)),
+            name_matching: NameMatching::Exact,
+            applied: Arc::new(Mutex::new(checkpoint)),
+            advanced: Arc::new(Notify::new()),
+        })
+    }
+
+    /// Choose how names are matched, re-keying any rows already stored
+    pub fn with_name_matching(mut self, name_matching: NameMatching) -> DomainResult<Self> {
+        self.name_matching = name_matching;
+        {
+            let mut conn = self.conn.lock().unwrap();
+            let tx = conn.transaction().map_err(db_error)?;
+            let names: Vec<(u32, String)> = tx
+                .prepare("SELECT id, name FROM users")
+                .and_then(|mut stmt| {
+                    stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                        .collect()
+                })
+                .map_err(db_error)?;
+            for (id, name) in names {
+                tx.execute(
+                    "UPDATE users SET name_key = ?1 WHERE id = ?2",
+                    params![name_matching.key(&name), id],
+                )
+                .map_err(db_error)?;
+            }
+            tx.commit().map_err(db_error)?;
+        }
+        Ok(self)
+    }
+
+    /// Global position of the last applied envelope (None before any)
+    pub fn checkpoint(&self) -> DomainResult<Option<u64>> {
+        let conn = self.conn.lock().unwrap();
+        read_checkpoint(&conn).map_err(db_error)
+    }
+
+    /// Whether the event at `position` has been applied
+    pub fn has_applied(&self, position: u64) -> DomainResult<bool> {
+        let applied = *self.applied.lock().unwrap();
+        Ok(applied.is_some_and(|checkpoint| checkpoint >= position))
+    }
+
+    /// Apply a stored event at most once; returns whether it changed anything
+    pub fn apply(&self, envelope: &EventEnvelope) -> DomainResult<bool> {
+        Ok(self.apply_batch(std::slice::from_ref(envelope))? == 1)
+    }
+
+    /// Apply stored events in one transaction, skipping any at or before the
+    /// checkpoint; returns how many were applied
+    pub fn apply_batch(&self, envelopes: &[EventEnvelope]) -> DomainResult<usize> {
+        let applied = {
+            let mut conn = self.conn.lock().unwrap();
+            let tx = conn.transaction().map_err(db_error)?;
+            let mut checkpoint = read_checkpoint(&tx).map_err(db_error)?;
+            let mut applied = 0;
+            for envelope in envelopes {
+                if checkpoint.is_some_and(|position| envelope.global_position <= position) {
+                    continue;
+                }
+                self.apply_in(&tx, &envelope.event).map_err(db_error)?;
+                checkpoint = Some(envelope.global_position);
+                applied += 1;
+            }
+            if let Some(position) = checkpoint.filter(|_| applied > 0) {
+                tx.execute(
+                    "INSERT INTO checkpoint (id, position) VALUES (0, ?1)
+                     ON CONFLICT (id) DO UPDATE SET position = excluded.position",
+                    params![position],
+                )
+                .map_err(db_error)?;
+            }
+            tx.commit().map_err(db_error)?;
+            *self.applied.lock().unwrap() = checkpoint;
+            applied
+        };
+        if applied > 0 {
+            self.advanced.notify_waiters();
+        }
+        Ok(applied)
+    }
+
+    /// Apply every stored event after the checkpoint, a batch per transaction
+    pub fn catch_up(&self, event_store: &EventStore) -> DomainResult<usize> {
+        let mut total = 0;
+        loop {
+            let from = self.checkpoint()?.map_or(0, |position| position + 1);
+            let batch = event_store.read_batch(from, REBUILD_BATCH_SIZE);
+            total += self.apply_batch(&batch)?;
+            if batch.len() < REBUILD_BATCH_SIZE {
+                return Ok(total);
+            }
+        }
+    }
+
+    /// Tie this database to `event_store`, first deleting everything in it
+    /// if it was built from another log (or from one it cannot identify);
+    /// returns whether it was cleared
+    pub fn attach(&self, event_store: &EventStore) -> DomainResult<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(db_error)?;
+        let source: Option<String> = tx
+            .query_row("SELECT log_id FROM source_log WHERE id = 0", [], |row| row.get(0))
+            .optional()
+            .map_err(db_error)?;
+        let checkpoint = read_checkpoint(&tx).map_err(db_error)?;
+        let stale = match source {
+            Some(log_id) => log_id != event_store.log_id(),
+            None => checkpoint.is_some(),
+        };
+        if stale {
+            clear(&tx).map_err(db_error)?;
+        }
+        tx.execute(
+            "INSERT INTO source_log (id, log_id) VALUES (0, ?1)
+             ON CONFLICT (id) DO UPDATE SET log_id = excluded.log_id",
+            params![event_store.log_id()],
+        )
+        .map_err(db_error)?;
+        tx.commit().map_err(db_error)?;
+        if stale {
+            *self.applied.lock().unwrap() = None;
+        }
+        Ok(stale)
+    }
+
+    /// Delete every row and the checkpoint, ready to rebuild from the log
+    pub fn reset(&self) -> DomainResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(db_error)?;
+        clear(&tx).map_err(db_error)?;
+        tx.commit().map_err(db_error)?;
+        *self.applied.lock().unwrap() = None;
+        Ok(())
+    }
+
+    /// Update the read model for one event, without checkpointing
+    pub fn apply_event(&self, event: &UserEvent) -> DomainResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(db_error)?;
+        self.apply_in(&tx, event).map_err(db_error)?;
+        tx.commit().map_err(db_error)
+    }
+
+    fn apply_in(&self, tx: &Transaction<'_>, event: &UserEvent) -> rusqlite::Result<()> {
+        let user_id = event.aggregate_id();
+        match event {
+            UserEvent::Registered { name, timestamp, .. } => {
+                let user = match load_user(tx, user_id)? {
+                    Some(mut previous) => {
+                        previous.record(event);
+                        previous
+                    }
+                    None => UserReadModel::registered(user_id, name.clone(), *timestamp),
+                };
+                self.store_user(tx, &user)?;
+                tx.execute("DELETE FROM redirects WHERE user_id = ?1", params![user_id])?;
+            }
+            UserEvent::Renamed { .. } | UserEvent::Absorbed { .. } => {
+                if let Some(mut user) = load_user(tx, user_id)? {
+                    user.record(event);
+                    self.store_user(tx, &user)?;
+                }
+            }
+            UserEvent::MergedInto { target_id, .. } => {
+                tx.execute("DELETE FROM users WHERE id = ?1", params![user_id])?;
+                tx.execute(
+                    "INSERT INTO redirects (user_id, target_id) VALUES (?1, ?2)
+                     ON CONFLICT (user_id) DO UPDATE SET target_id = excluded.target_id",
+                    params![user_id, target_id],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn store_user(&self, tx: &Transaction<'_>, user: &UserReadModel) -> rusqlite::Result<()> {
+        tx.execute(
+            "INSERT INTO users (id, name, name_key, created_at, version, updated_at, rename_count)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT (id) DO UPDATE SET
+                 name = excluded.name,
+                 name_key = excluded.name_key,
+                 created_at = excluded.created_at,
+                 version = excluded.version,
+                 updated_at = excluded.updated_at,
+                 rename_count = excluded.rename_count",
+            params![
+                user.id,
+                user.name,
+                self.name_matching.key(&user.name),
+                user.created_at,
+                user.version,
+                user.updated_at,
+                user.rename_count,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Look up a user, following merge redirects to the surviving account
+    pub fn get_user(&self, user_id: u32) -> DomainResult<Option<UserReadModel>> {
+        let user_id = self.resolve_id(user_id)?;
+        let conn = self.conn.lock().unwrap();
+        load_user(&conn, user_id).map_err(db_error)
+    }
+
+    /// The ID a lookup for `user_id` ends up at after following merges
+    pub fn resolve_id(&self, user_id: u32) -> DomainResult<u32> {
+        let conn = self.conn.lock().unwrap();
+        let redirects: u32 = conn
+            .query_row("SELECT COUNT(*) FROM redirects", [], |row| row.get(0))
+            .map_err(db_error)?;
+        let mut current = user_id;
+        // Bounded walk guards against a corrupt redirect cycle
+        for _ in 0..=redirects {
+            let target = conn
+                .query_row(
+                    "SELECT target_id FROM redirects WHERE user_id = ?1",
+                    params![current],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(db_error)?;
+            match target {
+                Some(target) => current = target,
+                None => break,
+            }
+        }
+        Ok(current)
+    }
+
+    /// All users, ordered by ID so callers see a stable sequence
+    pub fn get_all_users(&self) -> DomainResult<Vec<UserReadModel>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(&format!("SELECT {} FROM users ORDER BY id", USER_COLUMNS))
+            .map_err(db_error)?;
+        let users = stmt
+            .query_map([], user_from_row)
+            .and_then(|rows| rows.collect())
+            .map_err(db_error);
+        users
+    }
+
+    /// Look up a user by name; if several share a key, the latest one named wins
+    pub fn find_by_name(&self, name: &str) -> DomainResult<Option<UserReadModel>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            &format!(
+                "SELECT {} FROM users WHERE name_key = ?1
+                 ORDER BY updated_at DESC, id DESC LIMIT 1",
+                USER_COLUMNS
+            ),
+            params![self.name_matching.key(name)],
+            user_from_row,
+        )
+        .optional()
+        .map_err(db_error)
+    }
+
+    /// List users in a stable order, filtered and paged by cursor
+    /// Filtering, ordering and the page limit all run in SQL.
+    pub fn list_users(&self, query: &UserListQuery) -> DomainResult<UserPage> {
+        query.check_cursor()?;
+
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+        if let Some(prefix) = &query.name_prefix {
+            let key = self.name_matching.key(prefix);
+            conditions.push("substr(name_key, 1, ?) = ?".to_string());
+            values.push(Value::Integer(key.chars().count() as i64));
+            values.push(Value::Text(key));
+        }
+        if let Some(from) = query.created_from {
+            conditions.push("created_at >= ?".to_string());
+            values.push(Value::Integer(from));
+        }
+        if let Some(to) = query.created_to {
+            conditions.push("created_at < ?".to_string());
+            values.push(Value::Integer(to));
+        }
+
+        let column = sort_column(query.sort);
+        let (comparison, direction) = if query.descending { ("<", "DESC") } else { (">", "ASC") };
+        if let Some(cursor) = &query.after {
+            let last = cursor.last();
+            match query.sort {
+                UserSortKey::Id => conditions.push(format!("id {} ?", comparison)),
+                UserSortKey::Name => {
+                    conditions.push(format!("(name, id) {} (?, ?)", comparison));
+                    values.push(Value::Text(last.name.clone()));
+                }
+                UserSortKey::CreatedAt => {
+                    conditions.push(format!("(created_at, id) {} (?, ?)", comparison));
+                    values.push(Value::Integer(last.created_at));
+                }
+            }
+            values.push(Value::Integer(i64::from(last.id)));
+        }
+
+        let filter = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let order = match query.sort {
+            UserSortKey::Id => format!("id {}", direction),
+            _ => format!("{} {}, id {}", column, direction, direction),
+        };
+        // One row past the page tells whether there is a next one
+        values.push(Value::Integer(query.limit.saturating_add(1) as i64));
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM users {} ORDER BY {} LIMIT ?",
+                USER_COLUMNS, filter, order
+            ))
+            .map_err(db_error)?;
+        let users = stmt
+            .query_map(params_from_iter(values), user_from_row)
+            .and_then(|rows| rows.collect())
+            .map_err(db_error)?;
+        Ok(query.finish_page(users))
+    }
+
+    /// Run `query` on the blocking pool so database I/O never stalls the runtime
+    async fn blocking<T: Send + 'static>(
+        &self,
+        query: impl FnOnce(&SqliteUserProjection) -> DomainResult<T> + Send + 'static,
+    ) -> DomainResult<T> {
+        let projection = self.clone();
+        tokio::task::spawn_blocking(move || query(&projection))
+            .await
+            .map_err(|err| AppError::RepositoryError(format!("SQLite read model: {}", err)))?
+    }
+
+    /// Wait until the event at `position` has been applied, for at most `timeout`
+    pub async fn wait_for_position(&self, position: u64, timeout: Duration) -> bool {
+        wait_until(&self.advanced, timeout, || {
+            self.has_applied(position).unwrap_or(false)
+        })
+        .await
+    }
+}
+
+fn read_checkpoint(conn: &Connection) -> rusqlite::Result<Option<u64>> {
+    conn.query_row("SELECT position FROM checkpoint WHERE id = 0", [], |row| row.get(0))
+        .optional()
+}
+
+fn clear(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "DELETE FROM users; DELETE FROM redirects; DELETE FROM checkpoint; DELETE FROM source_log;",
+    )
+}
+
+fn load_user(conn: &Connection, user_id: u32) -> rusqlite::Result<Option<UserReadModel>> {
+    conn.query_row(
+        &format!("SELECT {} FROM users WHERE id = ?1", USER_COLUMNS),
+        params![user_id],
+        user_from_row,
+    )
+    .optional()
+}
+
+impl Clone for SqliteUserProjection {
+    fn clone(&self) -> Self {
+        SqliteUserProjection {
+            conn: Arc::clone(&self.conn),
+            name_matching: self.name_matching,
+            applied: Arc::clone(&self.applied),
+            advanced: Arc::clone(&self.advanced),
+        }
+    }
+}
+
+#[async_trait]
+impl UserQueries for SqliteUserProjection {
+    async fn get_user(&self, user_id: u32) -> DomainResult<Option<UserReadModel>> {
+        self.blocking(move |sqlite| sqlite.get_user(user_id)).await
+    }
+
+    async fn resolve_id(&self, user_id: u32) -> DomainResult<u32> {
+        self.blocking(move |sqlite| sqlite.resolve_id(user_id)).await
+    }
+
+    async fn get_all_users(&self) -> DomainResult<Vec<UserReadModel>> {
+        self.blocking(|sqlite| sqlite.get_all_users()).await
+    }
+
+    async fn find_by_name(&self, name: &str) -> DomainResult<Option<UserReadModel>> {
+        let name = name.to_string();
+        self.blocking(move |sqlite| sqlite.find_by_name(&name)).await
+    }
+
+    async fn list_users(&self, query: &UserListQuery) -> DomainResult<UserPage> {
+        let query = query.clone();
+        self.blocking(move |sqlite| sqlite.list_users(&query)).await
+    }
+
+    async fn checkpoint(&self) -> DomainResult<Option<u64>> {
+        self.blocking(|sqlite| sqlite.checkpoint()).await
+    }
+
+    async fn wait_for_position(&self, position: u64, timeout: Duration) -> bool {
+        SqliteUserProjection::wait_for_position(self, position, timeout).await
+    }
+}
@@ -0,0 +1,223 @@
+// Stats - Time-bucketed user analytics
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use domain::errors::AppError;
+use domain::events::{EventEnvelope, UserEvent};
+use crate::event_store::EventStore;
+use super::Handles;
+
+const HOUR_MILLIS: i64 = 3_600_000;
+
+/// StatsBucket - Width of the time buckets counters are reported in
+/// Buckets are aligned to the Unix epoch (UTC), so weeks start on Thursdays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatsBucket {
+    Hour,
+    #[default]
+    Day,
+    Week,
+}
+
+impl StatsBucket {
+    pub fn millis(&self) -> i64 {
+        match self {
+            StatsBucket::Hour => HOUR_MILLIS,
+            StatsBucket::Day => 24 * HOUR_MILLIS,
+            StatsBucket::Week => 7 * 24 * HOUR_MILLIS,
+        }
+    }
+
+    /// Start of the bucket `timestamp` falls in
+    pub fn start_of(&self, timestamp: i64) -> i64 {
+        timestamp - timestamp.rem_euclid(self.millis())
+    }
+}
+
+impl std::str::FromStr for StatsBucket {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "hour" => Ok(StatsBucket::Hour),
+            "day" => Ok(StatsBucket::Day),
+            "week" => Ok(StatsBucket::Week),
+            other => Err(AppError::Validation(format!(
+                "Unknown bucket '{}' (expected hour, day or week)",
+                other
+            ))),
+        }
+    }
+}
+
+/// BucketCounts - What happened to users within one time bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BucketCounts {
+    /// Start of the bucket (Unix timestamp in milliseconds)
+    pub start: i64,
+    pub signups: u64,
+    pub renames: u64,
+    /// Accounts merged away into another
+    pub merges: u64,
+}
+
+/// RenamedUser - A user ranked by how often they have been renamed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenamedUser {
+    pub user_id: u32,
+    pub name: String,
+    pub rename_count: u32,
+}
+
+/// UserStats - Snapshot of the analytics projection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserStats {
+    pub bucket: StatsBucket,
+    /// Users with a live account
+    pub active: u64,
+    /// Users deactivated by being merged into another account
+    pub deactivated: u64,
+    /// Counters per bucket, oldest first; buckets with no activity are omitted
+    pub buckets: Vec<BucketCounts>,
+    /// Active users with at least one rename, most renamed first
+    pub most_renamed: Vec<RenamedUser>,
+}
+
+/// UserStatsProjection - Signup, rename and merge counters over time
+/// Counters are kept per hour and rolled up to the requested bucket size
+/// when read, so changing the bucket size needs no rebuild.
+#[derive(Clone, Default)]
+pub struct UserStatsProjection {
+    state: Arc<Mutex<StatsState>>,
+}
+
+#[derive(Default)]
+struct StatsState {
+    /// Start of hour -> counters for that hour
+    hourly: BTreeMap<i64, BucketCounts>,
+    /// Active user ID -> (current name, rename count)
+    users: HashMap<u32, (String, u32)>,
+    deactivated: u64,
+    /// Global position of the last applied envelope
+    checkpoint: Option<u64>,
+}
+
+impl StatsState {
+    fn hour(&mut self, timestamp: i64) -> &mut BucketCounts {
+        let start = StatsBucket::Hour.start_of(timestamp);
+        self.hourly.entry(start).or_insert(BucketCounts { start, ..BucketCounts::default() })
+    }
+
+    fn apply_event(&mut self, event: &UserEvent) {
+        match event {
+            UserEvent::Registered { user_id, name, timestamp } => {
+                self.hour(*timestamp).signups += 1;
+                self.users.entry(*user_id).or_insert((String::new(), 0)).0 = name.clone();
+            }
+            UserEvent::Renamed { user_id, new_name, timestamp } => {
+                self.hour(*timestamp).renames += 1;
+                if let Some((name, renames)) = self.users.get_mut(user_id) {
+                    *name = new_name.clone();
+                    *renames += 1;
+                }
+            }
+            UserEvent::MergedInto { user_id, timestamp, .. } => {
+                self.hour(*timestamp).merges += 1;
+                if self.users.remove(user_id).is_some() {
+                    self.deactivated += 1;
+                }
+            }
+            UserEvent::Absorbed { .. } => {}
+        }
+    }
+}
+
+impl UserStatsProjection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Global position of the last applied envelope (None before any)
+    pub fn checkpoint(&self) -> Option<u64> {
+        self.state.lock().unwrap().checkpoint
+    }
+
+    /// Apply a stored event at most once; returns whether it was applied
+    pub fn apply(&self, envelope: &EventEnvelope) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.checkpoint.is_some_and(|position| envelope.global_position <= position) {
+            return false;
+        }
+        state.apply_event(&envelope.event);
+        state.checkpoint = Some(envelope.global_position);
+        true
+    }
+
+    /// Apply every stored event after the checkpoint; returns how many were applied
+    pub fn catch_up(&self, event_store: &EventStore) -> usize {
+        let from = self.checkpoint().map_or(0, |position| position + 1);
+        event_store
+            .read_all_from(from)
+            .iter()
+            .filter(|envelope| self.apply(envelope))
+            .count()
+    }
+
+    /// Clear every counter and rebuild them from the whole event log
+    pub fn rebuild(&self, event_store: &EventStore) -> usize {
+        *self.state.lock().unwrap() = StatsState::default();
+        self.catch_up(event_store)
+    }
+
+    /// Update the counters for one event, without checkpointing
+    pub fn apply_event(&self, event: &UserEvent) {
+        self.state.lock().unwrap().apply_event(event);
+    }
+
+    /// Counters rolled up to `bucket`, with the `top` most renamed users
+    /// Ties in rename count are broken by user ID.
+    pub fn stats(&self, bucket: StatsBucket, top: usize) -> UserStats {
+        let state = self.state.lock().unwrap();
+
+        let mut buckets: BTreeMap<i64, BucketCounts> = BTreeMap::new();
+        for hour in state.hourly.values() {
+            let start = bucket.start_of(hour.start);
+            let counts = buckets.entry(start).or_insert(BucketCounts { start, ..BucketCounts::default() });
+            counts.signups += hour.signups;
+            counts.renames += hour.renames;
+            counts.merges += hour.merges;
+        }
+
+        let mut most_renamed: Vec<RenamedUser> = state
+            .users
+            .iter()
+            .filter(|(_, (_, renames))| *renames > 0)
+            .map(|(&user_id, (name, renames))| RenamedUser {
+                user_id,
+                name: name.clone(),
+                rename_count: *renames,
+            })
+            .collect();
+        most_renamed.sort_by(|a, b| b.rename_count.cmp(&a.rename_count).then(a.user_id.cmp(&b.user_id)));
+        most_renamed.truncate(top);
+
+        UserStats {
+            bucket,
+            active: state.users.len() as u64,
+            deactivated: state.deactivated,
+            buckets: buckets.into_values().collect(),
+            most_renamed,
+        }
+    }
+}
+
+impl Handles<UserEvent> for UserStatsProjection {
+    fn handle(&self, event: &UserEvent) {
+        self.apply_event(event);
+    }
+}
+
+impl Handles<EventEnvelope> for UserStatsProjection {
+    fn handle(&self, envelope: &EventEnvelope) {
+        self.apply(envelope);
+    }
+}
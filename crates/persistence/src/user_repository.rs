@@ -1,5 +1,5 @@
 // User Repository Implementation
-use domain::{User, events::UserEvent, errors::DomainResult, repository::IRepository};
+use domain::{User, events::{EventEnvelope, EventMetadata, UserEvent}, errors::DomainResult, repository::IRepository};
 use crate::event_store::EventStore;
 use crate::projections::UserProjection;
 
@@ -12,10 +12,14 @@ impl Repository {
     pub fn new(event_store: EventStore, projection: UserProjection) -> Self {
         Repository { event_store, projection }
     }
-}
 
-impl IRepository for Repository {
-    fn save(&self, aggregate: &User, expected_version: i32) -> DomainResult<Vec<UserEvent>> {
+    /// Save uncommitted changes, stamping each stored event with command metadata
+    pub fn save_with_metadata(
+        &self,
+        aggregate: &User,
+        expected_version: i32,
+        metadata: &EventMetadata,
+    ) -> DomainResult<Vec<EventEnvelope>> {
         let changes = aggregate.get_uncommitted_changes();
 
         if changes.is_empty() {
@@ -29,11 +33,88 @@ impl IRepository for Repository {
             });
         }
 
-        for event in changes.iter() {
-            self.event_store.append(aggregate.id, event.clone());
+        let envelopes = changes
+            .into_iter()
+            .map(|event| self.event_store.append_with_metadata(aggregate.id, event, metadata))
+            .collect();
+
+        Ok(envelopes)
+    }
+
+    /// Save uncommitted changes of several aggregates atomically
+    /// Each aggregate's stream must still be at the version it was loaded
+    /// at; either every change is stored or none is.
+    pub fn save_all_with_metadata(
+        &self,
+        saves: &[(&User, &EventMetadata)],
+    ) -> DomainResult<Vec<Vec<EventEnvelope>>> {
+        let batch = saves
+            .iter()
+            .map(|(aggregate, metadata)| {
+                (
+                    aggregate.id,
+                    aggregate.version,
+                    aggregate.get_uncommitted_changes(),
+                    (*metadata).clone(),
+                )
+            })
+            .collect();
+        self.event_store.append_batch(batch)
+    }
+
+    /// Full stream of stored envelopes for an aggregate
+    pub fn get_stream(&self, id: u32) -> DomainResult<Vec<EventEnvelope>> {
+        let envelopes = self.event_store.get_envelopes(id);
+
+        if envelopes.is_empty() {
+            return Err(domain::errors::AppError::AggregateNotFound(id));
+        }
+
+        Ok(envelopes)
+    }
+
+    /// Rebuild a user from the events recorded at or before `timestamp` (Unix millis)
+    pub fn get_by_id_as_of(&self, id: u32, timestamp: i64) -> DomainResult<User> {
+        let events: Vec<UserEvent> = self
+            .get_stream(id)?
+            .into_iter()
+            .take_while(|envelope| envelope.timestamp <= timestamp)
+            .map(|envelope| envelope.event)
+            .collect();
+
+        if events.is_empty() {
+            return Err(domain::errors::AppError::AggregateNotFound(id));
+        }
+
+        User::load_from_history(events)
+    }
+
+    /// Rebuild a user from its events up to and including `version`
+    pub fn get_by_id_at_version(&self, id: u32, version: i32) -> DomainResult<User> {
+        let stream = self.get_stream(id)?;
+        let current_version = stream.len() as i32 - 1;
+
+        if version < 0 || version > current_version {
+            return Err(domain::errors::AppError::Validation(format!(
+                "User {} has no version {} (current version is {})",
+                id, version, current_version
+            )));
         }
 
-        Ok(changes)
+        let events = stream
+            .into_iter()
+            .take_while(|envelope| envelope.event_version <= version)
+            .map(|envelope| envelope.event)
+            .collect();
+
+        User::load_from_history(events)
+    }
+}
+
+impl IRepository for Repository {
+    fn save(&self, aggregate: &User, expected_version: i32) -> DomainResult<Vec<UserEvent>> {
+        self.save_with_metadata(aggregate, expected_version, &EventMetadata::default())
+            .map(|envelopes| envelopes.into_iter().map(|envelope| envelope.event).collect())
     }
 
     fn get_by_id(&self, id: u32) -> DomainResult<User> {
@@ -50,7 +131,7 @@ impl IRepository for Repository {
         // Use the projection layer for efficient name lookups
         if let Some(read_model) = self.projection.find_by_name(name) {
             // If found in projection, reconstruct the full aggregate from events
-            self.get_by_id(read_model.id).map(|user| Some(user))
+            self.get_by_id(read_model.id).map(Some)
         } else {
             Ok(None)
         }
@@ -25,14 +25,18 @@ pub mod events {
     pub use ::application::EventHandler;
     
     pub mod event_bus {
-        pub use ::application::event_bus::{EventBus, HandlerPriority, PublishError, HandlerError};
+        pub use ::application::event_bus::{EventBus, HandlerPriority, PublishError, HandlerError, ReplayError, TargetFailure, TargetFailures};
+        pub use ::application::retry::{PermanentError, RetryPolicy};
     }
     
     pub mod projections {
-        use ::domain::events::UserEvent;
+        use ::domain::events::{EventEnvelope, UserEvent};
         use ::application::EventHandler;
         
         pub use ::persistence::projections::*;
+        pub use ::persistence::projections::search::bounded_edit_distance;
+        pub use ::application::{SearchProjectionEventHandler, SqliteProjectionEventHandler};
+        pub use ::application::{ManagedProjection, ProjectionInfo, ProjectionRegistry, ProjectionStatus};
         
         /// Adapter to make TypedUserProjectionHandler work with EventBus
         pub struct TypedUserProjectionHandlerAdapter {
@@ -52,6 +56,12 @@ pub mod events {
                 self.inner.handle(event);
                 Ok(())
             }
+
+            async fn handle_envelope(&self, envelope: &EventEnvelope) -> Result<(), Box<dyn std::error::Error>> {
+                use ::persistence::projections::Handles;
+                self.inner.handle(envelope);
+                Ok(())
+            }
             
             fn name(&self) -> &str {
                 "UserProjectionAdapter"
@@ -60,6 +70,23 @@ pub mod events {
     }
 }
 
+#[cfg(feature = "testing")]
+pub mod simulation {
+    pub use ::application::simulation::*;
+    pub use ::application::correlation::{CorrelationIdGenerator, SeededIdGenerator, TimestampIdGenerator};
+}
+
+#[cfg(feature = "testing")]
+pub mod testing {
+    pub mod aggregate {
+        pub use ::domain::testing::*;
+    }
+
+    pub mod handler {
+        pub use ::application::testing::*;
+    }
+}
+
 pub mod queries {
     use ::persistence::{UserProjection, projections::UserReadModel};
     
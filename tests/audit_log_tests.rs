@@ -0,0 +1,130 @@
+//! Audit log with actor attribution
+//!
+//! Commands carry the actor issuing them onto every event they store, and
+//! AuditLogProjection answers who changed which user, when, filtered by
+//! actor, user and time range.
+
+use std::sync::Arc;
+use rust_composition::{
+    commands::{MergeUsersCommand, RegisterUserCommand, RenameUserCommand},
+    events::projections::{AuditLogProjection, AuditQuery, ProjectionRegistry},
+    simulation::Simulation,
+};
+
+const START: i64 = Simulation::START_MILLIS;
+
+/// Simulation whose events also flow into a registered audit log
+fn with_audit() -> (Simulation, Arc<ProjectionRegistry>, AuditLogProjection) {
+    let sim = Simulation::new(1);
+    let registry = Arc::new(ProjectionRegistry::new(sim.event_store().clone()));
+    let audit = AuditLogProjection::new();
+    registry.register(Arc::new(audit.clone())).expect("Should register");
+    registry.start_all().expect("Should start");
+    sim.event_bus().subscribe(registry.clone());
+    (sim, registry, audit)
+}
+
+async fn register(sim: &Simulation, user_id: u32, name: &str, actor: &str) {
+    let command = RegisterUserCommand::new(user_id, name.to_string())
+        .expect("Valid command")
+        .with_actor(actor.to_string());
+    sim.command_handler().handle_register_user(command).await.expect("Register should succeed");
+    sim.advance_millis(1_000);
+}
+
+async fn rename(sim: &Simulation, user_id: u32, new_name: &str, actor: &str) {
+    let command = RenameUserCommand::new(user_id, new_name.to_string())
+        .expect("Valid command")
+        .with_actor(actor.to_string());
+    sim.command_handler().handle_rename_user(command).await.expect("Rename should succeed");
+    sim.advance_millis(1_000);
+}
+
+/// alice registers users 1 and 2, bob renames both, alice renames user 1
+async fn history(sim: &Simulation) {
+    register(sim, 1, "Ann", "alice").await;
+    register(sim, 2, "Ben", "alice").await;
+    rename(sim, 1, "Anna", "bob").await;
+    rename(sim, 2, "Benny", "bob").await;
+    rename(sim, 1, "Annie", "alice").await;
+}
+
+fn positions(audit: &AuditLogProjection, query: AuditQuery) -> Vec<u64> {
+    audit.query(&query).entries.iter().map(|entry| entry.position).collect()
+}
+
+#[tokio::test]
+async fn test_actor_is_recorded_on_stored_events() {
+    let (sim, _, audit) = with_audit();
+    history(&sim).await;
+    sim.command_handler()
+        .handle_merge_users(MergeUsersCommand::new(2, 1).expect("Valid command").with_actor("carol".to_string()))
+        .await
+        .expect("Merge should succeed");
+    sim.register_user(3, "Cat").await.expect("Register should succeed");
+
+    let actors: Vec<_> = sim.event_store().read_all_from(0).into_iter().map(|e| e.actor).collect();
+    assert_eq!(actors[5..], [Some("carol".to_string()), Some("carol".to_string()), None]);
+
+    let merge = audit.query(&AuditQuery { actor: Some("carol".to_string()), ..AuditQuery::default() });
+    let entries: Vec<_> = merge.entries.iter().map(|e| (e.user_id, e.event_type.as_str())).collect();
+    assert_eq!(entries, vec![(2, "UserMergedInto"), (1, "UserAbsorbed")]);
+    assert_eq!(merge.entries[0].correlation_id, merge.entries[1].correlation_id);
+    assert_eq!(audit.query(&AuditQuery::default()).entries[7].actor, None);
+}
+
+#[tokio::test]
+async fn test_filters_by_actor_user_and_time() {
+    let (sim, _, audit) = with_audit();
+    history(&sim).await;
+
+    let by = |actor: &str| AuditQuery { actor: Some(actor.to_string()), ..AuditQuery::default() };
+    assert_eq!(positions(&audit, by("alice")), vec![0, 1, 4]);
+    assert_eq!(positions(&audit, by("bob")), vec![2, 3]);
+    assert!(positions(&audit, by("mallory")).is_empty());
+
+    let user_one = AuditQuery { user_id: Some(1), ..AuditQuery::default() };
+    assert_eq!(positions(&audit, user_one.clone()), vec![0, 2, 4]);
+    assert_eq!(positions(&audit, AuditQuery { actor: Some("bob".to_string()), ..user_one }), vec![2]);
+
+    let window = AuditQuery { from: Some(START + 1_000), to: Some(START + 3_000), ..AuditQuery::default() };
+    assert_eq!(positions(&audit, window), vec![1, 2]);
+}
+
+#[tokio::test]
+async fn test_empty_or_inverted_window_matches_nothing() {
+    let (sim, _, audit) = with_audit();
+    history(&sim).await;
+
+    let window = |from: i64, to: i64| AuditQuery { from: Some(from), to: Some(to), ..AuditQuery::default() };
+    assert!(positions(&audit, window(START + 3_000, START + 1_000)).is_empty());
+    assert!(positions(&audit, window(START + 1_000, START + 1_000)).is_empty());
+    let inverted_for_user = AuditQuery { user_id: Some(1), ..window(START + 4_000, START) };
+    assert!(audit.query(&inverted_for_user).next_after.is_none());
+    assert!(positions(&audit, inverted_for_user).is_empty());
+}
+
+#[tokio::test]
+async fn test_pages_with_after() {
+    let (sim, _, audit) = with_audit();
+    history(&sim).await;
+
+    let first = audit.query(&AuditQuery { limit: 2, ..AuditQuery::default() });
+    assert_eq!(first.next_after, Some(1));
+
+    let rest = audit.query(&AuditQuery { limit: 3, after: first.next_after, ..AuditQuery::default() });
+    assert_eq!(rest.entries.iter().map(|e| e.position).collect::<Vec<_>>(), vec![2, 3, 4]);
+    assert_eq!(rest.next_after, None);
+}
+
+#[tokio::test]
+async fn test_rebuild_from_event_log() {
+    let (sim, registry, audit) = with_audit();
+    history(&sim).await;
+    let before = audit.query(&AuditQuery::default());
+
+    registry.rebuild("audit_log").expect("Should rebuild");
+
+    assert_eq!(audit.query(&AuditQuery::default()), before);
+    assert_eq!(before.entries[2].summary, "UserRenamed(id=1, new_name=Anna, timestamp=1700000002000)");
+}
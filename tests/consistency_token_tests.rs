@@ -0,0 +1,90 @@
+//! Read-your-writes consistency tokens
+//!
+//! A write's global position is its consistency token; readers can wait
+//! (with a bound) until the projection has applied that position.
+
+use std::time::Duration;
+use rust_composition::{
+    commands::RenameUserCommand,
+    events::{projections::UserProjection, EventStore, UserEvent},
+    simulation::Simulation,
+};
+
+fn registered(user_id: u32) -> UserEvent {
+    UserEvent::Registered {
+        user_id,
+        name: format!("User{}", user_id),
+        timestamp: 1000,
+    }
+}
+
+#[tokio::test]
+async fn test_write_is_visible_at_its_position() {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    sim.rename_user(1, "Alicia").await.expect("Rename should succeed");
+
+    let token = sim.event_store().get_envelopes(1).last().unwrap().global_position;
+
+    assert!(sim.projection().has_applied(token));
+    assert!(sim.projection().wait_for_position(token, Duration::ZERO).await);
+    assert_eq!(sim.projection().get_user(1).unwrap().name, "Alicia");
+}
+
+#[tokio::test]
+async fn test_command_returns_the_position_of_its_own_write() {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    let rename = RenameUserCommand::new(1, "Alicia".to_string()).expect("Valid command");
+
+    let saved = sim.command_handler().handle_rename_user(rename).await.expect("Rename should succeed");
+    sim.rename_user(1, "Ally").await.expect("Later rename should succeed");
+
+    assert_eq!(saved.len(), 1);
+    assert_eq!((saved[0].global_position, saved[0].event_version), (1, 1));
+    assert_eq!(sim.event_store().head_position(), 3, "A later write must not change the token");
+}
+
+#[tokio::test]
+async fn test_waiter_wakes_when_projection_catches_up() {
+    let store = EventStore::new();
+    let projection = UserProjection::new();
+    store.append(1, registered(1));
+    store.append(2, registered(2));
+    let token = store.head_position() - 1;
+    assert!(!projection.has_applied(token));
+
+    let (applier_store, applier_projection) = (store.clone(), projection.clone());
+    let applier = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        applier_projection.catch_up(&applier_store);
+    });
+
+    assert!(projection.wait_for_position(token, Duration::from_secs(5)).await);
+    assert!(projection.get_user(2).is_some());
+    applier.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_wait_gives_up_after_timeout() {
+    let store = EventStore::new();
+    let projection = UserProjection::new();
+    store.append(1, registered(1));
+    let token = store.head_position() - 1;
+
+    assert!(!projection.wait_for_position(token, Duration::from_millis(20)).await);
+
+    projection.catch_up(&store);
+    assert!(projection.wait_for_position(token, Duration::from_millis(20)).await);
+}
+
+#[tokio::test]
+async fn test_rebuild_keeps_applied_positions_visible() {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    let token = sim.event_store().head_position() - 1;
+
+    sim.projection().rebuild(sim.event_store()).expect("No rebuild should be running");
+
+    assert!(sim.projection().wait_for_position(token, Duration::ZERO).await);
+}
@@ -0,0 +1,185 @@
+//! Dead letter queue
+//!
+//! Stored events a handler still fails on land in the event store's dead
+//! letter queue with the handler's name and the envelope, and can be
+//! replayed to just that handler or discarded.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use rust_composition::{
+    domain::errors::AppError,
+    events::{
+        event_bus::{HandlerPriority, ReplayError},
+        EventEnvelope, EventHandler, UserEvent,
+    },
+    simulation::Simulation,
+};
+
+/// Fails every delivery while `broken` is set
+struct OutboxHandler {
+    broken: AtomicBool,
+    delivered: AtomicU32,
+}
+
+impl OutboxHandler {
+    fn broken() -> Arc<Self> {
+        Arc::new(OutboxHandler {
+            broken: AtomicBool::new(true),
+            delivered: AtomicU32::new(0),
+        })
+    }
+
+    fn fix(&self) {
+        self.broken.store(false, Ordering::SeqCst);
+    }
+
+    fn delivered(&self) -> u32 {
+        self.delivered.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait::async_trait]
+impl EventHandler for OutboxHandler {
+    async fn handle_event(&self, _event: &UserEvent) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+
+    async fn handle_envelope(&self, _envelope: &EventEnvelope) -> Result<(), Box<dyn std::error::Error>> {
+        if self.broken.load(Ordering::SeqCst) {
+            return Err("mail server unavailable".into());
+        }
+        self.delivered.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "Outbox"
+    }
+}
+
+/// Critical handler that fails every delivery
+struct LedgerHandler;
+
+#[async_trait::async_trait]
+impl EventHandler for LedgerHandler {
+    async fn handle_event(&self, _event: &UserEvent) -> Result<(), Box<dyn std::error::Error>> {
+        Err("ledger unavailable".into())
+    }
+
+    fn priority(&self) -> HandlerPriority {
+        HandlerPriority::Critical
+    }
+
+    fn name(&self) -> &str {
+        "Ledger"
+    }
+}
+
+/// Simulation with a broken outbox handler and one dead-lettered registration
+async fn with_dead_letter() -> (Simulation, Arc<OutboxHandler>, u64) {
+    let sim = Simulation::new(1);
+    let outbox = OutboxHandler::broken();
+    sim.event_bus().subscribe(outbox.clone());
+    sim.register_user(1, "Alice").await.expect("Non-critical failures should not fail the command");
+
+    let dead_letters = sim.event_store().get_dead_letter_queue();
+    assert_eq!(dead_letters.len(), 1);
+    let id = dead_letters[0].id;
+    (sim, outbox, id)
+}
+
+#[tokio::test]
+async fn test_failed_delivery_is_dead_lettered_with_handler_and_envelope() {
+    let (sim, _outbox, _id) = with_dead_letter().await;
+
+    let entry = &sim.event_store().get_dead_letter_queue()[0];
+    assert_eq!(entry.handler_name.as_deref(), Some("Outbox"));
+    assert_eq!(entry.aggregate_id, 1);
+    assert_eq!(entry.error_message, "mail server unavailable");
+    assert_eq!(entry.failure_count, 1);
+    let envelope = entry.envelope.as_ref().expect("Envelope should be kept for replay");
+    assert_eq!(envelope.global_position, 0);
+    assert_eq!(envelope.event, entry.event);
+}
+
+#[tokio::test]
+async fn test_replay_redelivers_to_the_handler_and_removes_the_entry() {
+    let (sim, outbox, id) = with_dead_letter().await;
+    outbox.fix();
+
+    sim.event_bus().replay_dead_letter(id).await.expect("Replay should succeed");
+
+    assert_eq!(outbox.delivered(), 1);
+    assert_eq!(sim.event_store().dlq_size(), 0);
+}
+
+#[tokio::test]
+async fn test_failed_replay_keeps_the_entry_and_counts_the_failure() {
+    let (sim, outbox, id) = with_dead_letter().await;
+
+    let result = sim.event_bus().replay_dead_letter(id).await;
+
+    assert!(matches!(result, Err(ReplayError::HandlerFailed(_))));
+    assert_eq!(outbox.delivered(), 0);
+    let entry = sim.event_store().get_dlq_entry(id).expect("Entry should stay queued");
+    assert_eq!(entry.failure_count, 2);
+}
+
+#[tokio::test]
+async fn test_discard_removes_the_entry_without_redelivering() {
+    let (sim, outbox, id) = with_dead_letter().await;
+    outbox.fix();
+
+    let discarded = sim.event_bus().discard_dead_letter(id).expect("Entry should exist");
+
+    assert_eq!(discarded.id, id);
+    assert_eq!(outbox.delivered(), 0);
+    assert_eq!(sim.event_store().dlq_size(), 0);
+    assert!(sim.event_bus().discard_dead_letter(id).is_none());
+}
+
+#[tokio::test]
+async fn test_unknown_entry_cannot_be_replayed() {
+    let (sim, _outbox, id) = with_dead_letter().await;
+
+    let result = sim.event_bus().replay_dead_letter(id + 1).await;
+
+    assert!(matches!(result, Err(ReplayError::NotFound(missing)) if missing == id + 1));
+}
+
+#[tokio::test]
+async fn test_handler_names_are_unique_per_bus() {
+    let (sim, _outbox, id) = with_dead_letter().await;
+    let impostor = OutboxHandler::broken();
+    impostor.fix();
+
+    let duplicate = sim.event_bus().try_subscribe(impostor.clone());
+    sim.event_bus().subscribe(impostor.clone());
+    sim.event_bus().replay_dead_letter(id).await.expect_err("Replay goes to the original handler");
+
+    assert!(matches!(duplicate, Err(AppError::Validation(_))));
+    assert_eq!(impostor.delivered(), 0);
+}
+
+#[tokio::test]
+async fn test_handlers_skipped_after_a_critical_failure_are_dead_lettered() {
+    let sim = Simulation::new(1);
+    let outbox = OutboxHandler::broken();
+    outbox.fix();
+    sim.event_bus().subscribe(outbox.clone());
+    sim.event_bus().subscribe(Arc::new(LedgerHandler));
+
+    let result = sim.register_user(1, "Alice").await;
+    assert!(result.is_err(), "Critical failures fail the command");
+    assert_eq!(outbox.delivered(), 0, "Lower-priority handlers are skipped");
+
+    let dead_letters = sim.event_store().get_dead_letter_queue();
+    let handlers: Vec<_> = dead_letters.iter().filter_map(|entry| entry.handler_name.as_deref()).collect();
+    assert_eq!(handlers, vec!["Ledger", "Outbox"]);
+    let skipped = &dead_letters[1];
+    assert_eq!(skipped.error_message, "Skipped after critical handler 'Ledger' failed");
+
+    sim.event_bus().replay_dead_letter(skipped.id).await.expect("Replay should succeed");
+    assert_eq!(outbox.delivered(), 1);
+    assert_eq!(sim.event_store().dlq_size(), 1, "Only the critical failure is left");
+}
@@ -0,0 +1,65 @@
+//! Dry-run command execution
+//!
+//! A dry run goes through validation, uniqueness and the aggregate, and
+//! reports the events it would produce without saving or publishing them.
+
+use rust_composition::{
+    commands::{RegisterUserCommand, RenameUserCommand},
+    events::UserEvent,
+    infrastructure::DomainError,
+    simulation::Simulation,
+};
+
+#[tokio::test]
+async fn test_dry_run_register_reports_events_without_saving() {
+    let sim = Simulation::new(1);
+    let cmd = RegisterUserCommand::new(1, "Alice".to_string()).expect("Valid command");
+
+    let events = sim.command_handler().dry_run_register_user(cmd).expect("Dry run should succeed");
+
+    assert_eq!(
+        events,
+        vec![UserEvent::Registered {
+            user_id: 1,
+            name: "Alice".to_string(),
+            timestamp: Simulation::START_MILLIS,
+        }]
+    );
+    assert_eq!(sim.event_store().event_count(), 0, "Dry run must not append events");
+    assert!(sim.projection().get_user(1).is_none(), "Dry run must not publish events");
+}
+
+#[tokio::test]
+async fn test_dry_run_register_surfaces_uniqueness_violation() {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    let cmd = RegisterUserCommand::new(2, "Alice".to_string()).expect("Valid command");
+
+    let result = sim.command_handler().dry_run_register_user(cmd);
+
+    assert!(matches!(result, Err(DomainError::Validation(msg)) if msg.contains("already taken")));
+}
+
+#[tokio::test]
+async fn test_dry_run_rename_leaves_state_untouched() {
+    let sim = Simulation::new(1);
+    sim.register_user(5, "Eve").await.expect("Register should succeed");
+    let cmd = RenameUserCommand::new(5, "Evelyn".to_string()).expect("Valid command");
+
+    let events = sim.command_handler().dry_run_rename_user(cmd).expect("Dry run should succeed");
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type(), "UserRenamed");
+    assert_eq!(sim.event_store().get_events(5).len(), 1, "Stream must be unchanged");
+    assert_eq!(sim.projection().get_user(5).expect("Should find user").name, "Eve");
+}
+
+#[tokio::test]
+async fn test_dry_run_rename_of_missing_user_fails() {
+    let sim = Simulation::new(1);
+    let cmd = RenameUserCommand::new(5, "X".to_string()).expect("Valid command");
+
+    let result = sim.command_handler().dry_run_rename_user(cmd);
+
+    assert_eq!(result, Err(DomainError::AggregateNotFound(5)));
+}
@@ -0,0 +1,108 @@
+//! Event bus dispatch order
+//!
+//! Handlers run from Critical down to Low, in subscription order within a
+//! level. A failing Critical handler fails the publish and lower-priority
+//! handlers never see the event; other failures stop nothing.
+
+use std::sync::{Arc, Mutex};
+use rust_composition::events::{
+    event_bus::{EventBus, HandlerPriority, PublishError},
+    EventHandler, UserEvent,
+};
+
+type CallLog = Arc<Mutex<Vec<String>>>;
+
+/// Records its name on every call, optionally failing afterwards
+struct RecordingHandler {
+    name: String,
+    priority: HandlerPriority,
+    fails: bool,
+    calls: CallLog,
+}
+
+#[async_trait::async_trait]
+impl EventHandler for RecordingHandler {
+    async fn handle_event(&self, _event: &UserEvent) -> Result<(), Box<dyn std::error::Error>> {
+        self.calls.lock().unwrap().push(self.name.clone());
+        if self.fails {
+            return Err(format!("{} failed", self.name).into());
+        }
+        Ok(())
+    }
+
+    fn priority(&self) -> HandlerPriority {
+        self.priority
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+fn subscribe(bus: &EventBus, calls: &CallLog, name: &str, priority: HandlerPriority, fails: bool) {
+    bus.subscribe(Arc::new(RecordingHandler {
+        name: name.to_string(),
+        priority,
+        fails,
+        calls: calls.clone(),
+    }));
+}
+
+fn registered() -> UserEvent {
+    UserEvent::Registered { user_id: 1, name: "Alice".to_string(), timestamp: 1000 }
+}
+
+fn calls(log: &CallLog) -> Vec<String> {
+    log.lock().unwrap().clone()
+}
+
+#[tokio::test]
+async fn test_handlers_run_in_priority_order_stable_within_a_level() {
+    let bus = EventBus::new();
+    let log = CallLog::default();
+    subscribe(&bus, &log, "low", HandlerPriority::Low, false);
+    subscribe(&bus, &log, "normal-1", HandlerPriority::Normal, false);
+    subscribe(&bus, &log, "critical-1", HandlerPriority::Critical, false);
+    subscribe(&bus, &log, "high", HandlerPriority::High, false);
+    subscribe(&bus, &log, "normal-2", HandlerPriority::Normal, false);
+    subscribe(&bus, &log, "critical-2", HandlerPriority::Critical, false);
+
+    let errors = bus.publish(&registered()).await.expect("Publish should succeed");
+
+    assert!(errors.is_empty());
+    assert_eq!(calls(&log), vec!["critical-1", "critical-2", "high", "normal-1", "normal-2", "low"]);
+}
+
+#[tokio::test]
+async fn test_critical_failure_skips_lower_priority_handlers() {
+    let bus = EventBus::new();
+    let log = CallLog::default();
+    subscribe(&bus, &log, "low", HandlerPriority::Low, false);
+    subscribe(&bus, &log, "high", HandlerPriority::High, false);
+    subscribe(&bus, &log, "critical-1", HandlerPriority::Critical, true);
+    subscribe(&bus, &log, "critical-2", HandlerPriority::Critical, false);
+
+    let result = bus.publish(&registered()).await;
+
+    match result {
+        Err(PublishError::CriticalHandlerFailed(err)) => assert_eq!(err.handler_name, "critical-1"),
+        other => panic!("Expected a critical failure, got {:?}", other),
+    }
+    assert_eq!(calls(&log), vec!["critical-1", "critical-2"], "Every critical handler still runs");
+}
+
+#[tokio::test]
+async fn test_non_critical_failure_does_not_stop_later_handlers() {
+    let bus = EventBus::new();
+    let log = CallLog::default();
+    subscribe(&bus, &log, "low", HandlerPriority::Low, false);
+    subscribe(&bus, &log, "high", HandlerPriority::High, true);
+    subscribe(&bus, &log, "normal", HandlerPriority::Normal, true);
+
+    let errors = bus.publish(&registered()).await.expect("Non-critical failures should not fail the publish");
+
+    let failed: Vec<&str> = errors.iter().map(|err| err.handler_name.as_str()).collect();
+    assert_eq!(failed, vec!["high", "normal"]);
+    assert!(errors.iter().all(|err| !err.is_critical));
+    assert_eq!(calls(&log), vec!["high", "normal", "low"]);
+}
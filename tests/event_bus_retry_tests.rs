@@ -0,0 +1,146 @@
+//! Event bus retries
+//!
+//! Handlers are retried under their retry policy with exponential backoff,
+//! permanent errors are not retried, and every attempt shows up in the
+//! bus's handler metrics.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use rust_composition::events::{
+    event_bus::{EventBus, HandlerPriority, PermanentError, PublishError, RetryPolicy},
+    EventHandler, UserEvent,
+};
+
+/// Fails the first `failures` deliveries, then succeeds
+struct FlakyHandler {
+    failures: u32,
+    calls: AtomicU32,
+    permanent: bool,
+    priority: HandlerPriority,
+    policy: Option<RetryPolicy>,
+}
+
+impl FlakyHandler {
+    fn failing(failures: u32) -> Self {
+        FlakyHandler {
+            failures,
+            calls: AtomicU32::new(0),
+            permanent: false,
+            priority: HandlerPriority::Normal,
+            policy: None,
+        }
+    }
+
+    fn calls(&self) -> u32 {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait::async_trait]
+impl EventHandler for FlakyHandler {
+    async fn handle_event(&self, _event: &UserEvent) -> Result<(), Box<dyn std::error::Error>> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        if call > self.failures {
+            Ok(())
+        } else if self.permanent {
+            Err(Box::new(PermanentError("malformed event".to_string())))
+        } else {
+            Err(format!("transient failure {}", call).into())
+        }
+    }
+
+    fn priority(&self) -> HandlerPriority {
+        self.priority
+    }
+
+    fn name(&self) -> &str {
+        "Flaky"
+    }
+
+    fn retry_policy(&self) -> Option<RetryPolicy> {
+        self.policy.clone()
+    }
+}
+
+fn fast(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy::new(max_attempts).with_backoff(Duration::from_millis(1), Duration::from_millis(5))
+}
+
+fn registered() -> UserEvent {
+    UserEvent::Registered { user_id: 1, name: "Alice".to_string(), timestamp: 1000 }
+}
+
+#[tokio::test]
+async fn test_handler_succeeds_after_transient_failures() {
+    let bus = EventBus::new().with_retry_policy(fast(3));
+    let handler = Arc::new(FlakyHandler::failing(2));
+    bus.subscribe(handler.clone());
+
+    let errors = bus.publish(&registered()).await.expect("Publish should succeed");
+
+    assert!(errors.is_empty());
+    assert_eq!(handler.calls(), 3);
+    let metrics = bus.metrics().get_handler_metrics("Flaky").unwrap();
+    assert_eq!((metrics.total_executions, metrics.failed_executions, metrics.successful_executions), (3, 2, 1));
+    assert_eq!((metrics.total_retries, metrics.successful_retries, metrics.failed_after_retries), (2, 1, 0));
+}
+
+#[tokio::test]
+async fn test_gives_up_after_max_attempts() {
+    let bus = EventBus::new().with_retry_policy(fast(3));
+    let handler = Arc::new(FlakyHandler::failing(5));
+    bus.subscribe(handler.clone());
+
+    let errors = bus.publish(&registered()).await.expect("Non-critical failures are reported");
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].error_message, "transient failure 3");
+    assert_eq!(handler.calls(), 3);
+    let metrics = bus.metrics().get_handler_metrics("Flaky").unwrap();
+    assert_eq!((metrics.total_retries, metrics.successful_retries, metrics.failed_after_retries), (2, 0, 1));
+}
+
+#[tokio::test]
+async fn test_permanent_errors_are_not_retried() {
+    let bus = EventBus::new().with_retry_policy(fast(5));
+    let handler = Arc::new(FlakyHandler { permanent: true, ..FlakyHandler::failing(1) });
+    bus.subscribe(handler.clone());
+
+    let errors = bus.publish(&registered()).await.expect("Publish should succeed");
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(handler.calls(), 1);
+    assert_eq!(bus.metrics().get_handler_metrics("Flaky").unwrap().total_retries, 0);
+}
+
+#[tokio::test]
+async fn test_handler_policy_overrides_bus_default() {
+    let bus = EventBus::new();
+    let handler = Arc::new(FlakyHandler {
+        priority: HandlerPriority::Critical,
+        policy: Some(fast(2)),
+        ..FlakyHandler::failing(1)
+    });
+    bus.subscribe(handler.clone());
+    let without_policy = Arc::new(FlakyHandler::failing(1));
+    let other_bus = EventBus::new();
+    other_bus.subscribe(without_policy.clone());
+
+    assert!(bus.publish(&registered()).await.is_ok(), "Critical handler recovered on retry");
+    assert_eq!(handler.calls(), 2);
+    assert_eq!(other_bus.publish(&registered()).await.unwrap().len(), 1, "Bus default is no retries");
+    assert_eq!(without_policy.calls(), 1);
+}
+
+#[tokio::test]
+async fn test_critical_handler_fails_publish_after_retries() {
+    let bus = EventBus::new().with_retry_policy(fast(2));
+    let handler = Arc::new(FlakyHandler { priority: HandlerPriority::Critical, ..FlakyHandler::failing(3) });
+    bus.subscribe(handler.clone());
+
+    let result = bus.publish(&registered()).await;
+
+    assert!(matches!(result, Err(PublishError::CriticalHandlerFailed(_))));
+    assert_eq!(handler.calls(), 2);
+}
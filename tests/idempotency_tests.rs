@@ -0,0 +1,148 @@
+//! Idempotent command handling
+//!
+//! Retries carrying the same idempotency key must return the original
+//! outcome without appending events a second time.
+
+use std::sync::Arc;
+use rust_composition::{
+    commands::{RegisterUserCommand, RenameUserCommand},
+    events::{EventHandler, UserEvent},
+    infrastructure::DomainError,
+    simulation::Simulation,
+};
+
+fn register(user_id: u32, name: &str) -> RegisterUserCommand {
+    RegisterUserCommand::new(user_id, name.to_string()).expect("Valid command")
+}
+
+#[tokio::test]
+async fn test_retried_registration_returns_original_result() {
+    let sim = Simulation::new(1);
+    let handler = sim.command_handler();
+
+    let first = handler
+        .handle_register_user(register(1, "Alice").with_idempotency_key("req-1".to_string()))
+        .await
+        .expect("First attempt should succeed");
+    let retry = handler
+        .handle_register_user(register(1, "Alice").with_idempotency_key("req-1".to_string()))
+        .await;
+
+    assert_eq!(retry, Ok(first), "Retry should replay the original envelopes");
+    assert_eq!(sim.event_store().event_count(), 1, "Retry must not append events");
+}
+
+#[tokio::test]
+async fn test_registration_without_key_is_not_deduplicated() {
+    let sim = Simulation::new(1);
+    let handler = sim.command_handler();
+
+    handler.handle_register_user(register(1, "Alice")).await.expect("First attempt should succeed");
+    let retry = handler.handle_register_user(register(2, "Alice")).await;
+
+    assert!(matches!(retry, Err(DomainError::Validation(msg)) if msg.contains("already taken")));
+}
+
+#[tokio::test]
+async fn test_retried_rename_is_applied_once() {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    let handler = sim.command_handler();
+
+    for _ in 0..3 {
+        let cmd = RenameUserCommand::new(1, "Alicia".to_string())
+            .expect("Valid command")
+            .with_idempotency_key("rename-1".to_string());
+        handler.handle_rename_user(cmd).await.expect("Rename should succeed");
+    }
+
+    assert_eq!(sim.event_store().get_events(1).len(), 2, "Only one Renamed event expected");
+}
+
+#[tokio::test]
+async fn test_key_reused_for_different_command_is_rejected() {
+    let sim = Simulation::new(1);
+    let handler = sim.command_handler();
+
+    handler
+        .handle_register_user(register(1, "Alice").with_idempotency_key("req-1".to_string()))
+        .await
+        .expect("First attempt should succeed");
+    let result = handler
+        .handle_register_user(register(2, "Bob").with_idempotency_key("req-1".to_string()))
+        .await;
+
+    assert!(matches!(result, Err(DomainError::Validation(msg)) if msg.contains("req-1")));
+    assert!(sim.projection().get_user(2).is_none(), "Rejected command must not execute");
+}
+
+#[tokio::test]
+async fn test_key_expires_after_retention_window() {
+    let sim = Simulation::new(1);
+    let handler = sim.command_handler();
+
+    handler
+        .handle_register_user(register(1, "Alice").with_idempotency_key("req-1".to_string()))
+        .await
+        .expect("First attempt should succeed");
+    sim.advance_millis(25 * 60 * 60 * 1000);
+    let late_retry = handler
+        .handle_register_user(register(1, "Alice").with_idempotency_key("req-1".to_string()))
+        .await;
+
+    assert!(
+        matches!(late_retry, Err(DomainError::Validation(msg)) if msg.contains("already taken")),
+        "Expired keys execute again"
+    );
+}
+
+/// Yields to the runtime on every event, so concurrent commands interleave
+struct YieldingHandler;
+
+#[async_trait::async_trait]
+impl EventHandler for YieldingHandler {
+    async fn handle_event(&self, _event: &UserEvent) -> Result<(), Box<dyn std::error::Error>> {
+        for _ in 0..3 {
+            tokio::task::yield_now().await;
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_concurrent_retries_wait_for_the_first_execution() {
+    let sim = Simulation::new(1);
+    sim.event_bus().subscribe(Arc::new(YieldingHandler));
+    let handler = sim.command_handler();
+
+    let (first, retry) = tokio::join!(
+        handler.handle_register_user(register(1, "Alice").with_idempotency_key("req-1".to_string())),
+        handler.handle_register_user(register(1, "Alice").with_idempotency_key("req-1".to_string())),
+    );
+
+    assert!(first.is_ok());
+    assert_eq!(retry, first, "Concurrent retry should get the original success");
+    assert_eq!(sim.event_store().event_count(), 1, "Command must execute once");
+}
+
+#[tokio::test]
+async fn test_retried_failure_replays_the_original_error() {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    let handler = sim.command_handler();
+
+    let first = handler
+        .handle_register_user(register(2, "Alice").with_idempotency_key("req-2".to_string()))
+        .await;
+    sim.command_handler()
+        .handle_rename_user(RenameUserCommand::new(1, "Alicia".to_string()).expect("Valid command"))
+        .await
+        .expect("Rename should succeed");
+    let retry = handler
+        .handle_register_user(register(2, "Alice").with_idempotency_key("req-2".to_string()))
+        .await;
+
+    assert!(matches!(first, Err(DomainError::Validation(ref msg)) if msg.contains("already taken")));
+    assert_eq!(retry, first, "Retry should replay the failure, not execute again");
+    assert!(sim.projection().get_user(2).is_none());
+}
@@ -0,0 +1,112 @@
+//! Merging duplicate user accounts
+//!
+//! The source records MergedInto, the target records Absorbed. Lookups of
+//! the source ID redirect to the target and the source's name is released.
+
+use rust_composition::{
+    commands::MergeUsersCommand,
+    domain::IRepository,
+    events::{EventMetadata, UserEvent},
+    infrastructure::DomainError,
+    simulation::Simulation,
+};
+
+async fn merged_pair() -> Simulation {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    sim.register_user(2, "Alice J").await.expect("Register should succeed");
+    sim.command_handler()
+        .handle_merge_users(MergeUsersCommand::new(2, 1).expect("Valid command"))
+        .await
+        .expect("Merge should succeed");
+    sim
+}
+
+#[tokio::test]
+async fn test_merge_records_events_on_both_streams() {
+    let sim = merged_pair().await;
+
+    let source = sim.event_store().get_envelopes(2);
+    let target = sim.event_store().get_envelopes(1);
+
+    assert!(matches!(
+        source.last().map(|e| &e.event),
+        Some(UserEvent::MergedInto { user_id: 2, target_id: 1, .. })
+    ));
+    assert!(matches!(
+        target.last().map(|e| &e.event),
+        Some(UserEvent::Absorbed { user_id: 1, source_id: 2, .. })
+    ));
+    assert_eq!(target.last().unwrap().causation_id, Some("User-2-1".to_string()));
+    assert_eq!(source.last().unwrap().correlation_id, target.last().unwrap().correlation_id);
+}
+
+#[tokio::test]
+async fn test_projection_redirects_source_to_target() {
+    let sim = merged_pair().await;
+    let projection = sim.projection();
+
+    assert_eq!(projection.resolve_id(2), 1);
+    assert_eq!(projection.get_user(2).expect("Should redirect").id, 1);
+    assert_eq!(projection.get_all_users().len(), 1);
+}
+
+#[tokio::test]
+async fn test_merge_releases_source_name() {
+    let sim = merged_pair().await;
+
+    assert!(sim.projection().find_by_name("Alice J").is_none());
+    sim.register_user(3, "Alice J")
+        .await
+        .expect("Released name should be available");
+}
+
+#[tokio::test]
+async fn test_merged_user_cannot_be_renamed_or_merged_again() {
+    let sim = merged_pair().await;
+    sim.register_user(3, "Carol").await.expect("Register should succeed");
+
+    let rename = sim.rename_user(2, "Alicia").await;
+    assert!(matches!(rename, Err(DomainError::Validation(msg)) if msg.contains("merged into user 1")));
+
+    let merge = sim
+        .command_handler()
+        .handle_merge_users(MergeUsersCommand::new(2, 3).expect("Valid command"))
+        .await;
+    assert!(matches!(merge, Err(DomainError::Validation(_))));
+}
+
+#[tokio::test]
+async fn test_merge_requires_distinct_existing_users() {
+    assert!(MergeUsersCommand::new(1, 1).is_err());
+
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+
+    let result = sim
+        .command_handler()
+        .handle_merge_users(MergeUsersCommand::new(1, 9).expect("Valid command"))
+        .await;
+
+    assert_eq!(result, Err(DomainError::AggregateNotFound(9)));
+    assert_eq!(sim.event_store().get_events(1).len(), 1);
+}
+
+#[tokio::test]
+async fn test_merge_batch_stores_nothing_if_a_stream_moved_on() {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    sim.register_user(2, "Alice J").await.expect("Register should succeed");
+    let repository = sim.repository();
+    let mut source = repository.get_by_id(2).expect("Source exists");
+    let mut target = repository.get_by_id(1).expect("Target exists");
+    source.merge_into(&mut target, sim.clock()).expect("Merge is valid");
+
+    sim.rename_user(1, "Alicia").await.expect("Concurrent rename should succeed");
+    let metadata = EventMetadata::default();
+    let result = repository.save_all_with_metadata(&[(&source, &metadata), (&target, &metadata)]);
+
+    assert!(matches!(result, Err(DomainError::ConcurrencyViolation { .. })));
+    assert_eq!(sim.event_store().get_envelopes(2).len(), 1, "Source must not be half-merged");
+    assert_eq!(sim.event_store().get_envelopes(1).len(), 2);
+}
@@ -0,0 +1,106 @@
+//! Secondary name index in UserProjection
+//!
+//! Name lookups go through a maintained `name -> id` index instead of
+//! scanning every user, optionally comparing names case-insensitively.
+
+use std::time::{Duration, Instant};
+use rust_composition::events::{
+    projections::{NameMatching, UserProjection},
+    UserEvent,
+};
+
+fn register(projection: &UserProjection, user_id: u32, name: &str) {
+    projection.apply_event(&UserEvent::Registered {
+        user_id,
+        name: name.to_string(),
+        timestamp: 1000,
+    });
+}
+
+fn rename(projection: &UserProjection, user_id: u32, new_name: &str) {
+    projection.apply_event(&UserEvent::Renamed {
+        user_id,
+        new_name: new_name.to_string(),
+        timestamp: 2000,
+    });
+}
+
+#[test]
+fn test_index_follows_renames() {
+    let projection = UserProjection::new();
+    register(&projection, 1, "Alice");
+    register(&projection, 2, "Bob");
+
+    rename(&projection, 1, "Alicia");
+
+    assert!(projection.find_by_name("Alice").is_none());
+    assert_eq!(projection.find_by_name("Alicia").map(|u| u.id), Some(1));
+    assert_eq!(projection.find_by_name("Bob").map(|u| u.id), Some(2));
+    assert!(projection.find_by_name("alicia").is_none(), "Exact matching is the default");
+}
+
+#[test]
+fn test_released_name_can_be_reused_by_another_user() {
+    let projection = UserProjection::new();
+    register(&projection, 1, "Alice");
+    rename(&projection, 1, "Alicia");
+    register(&projection, 2, "Alice");
+
+    assert_eq!(projection.find_by_name("Alice").map(|u| u.id), Some(2));
+    assert_eq!(projection.find_by_name("Alicia").map(|u| u.id), Some(1));
+}
+
+#[test]
+fn test_case_insensitive_matching_folds_names() {
+    let projection = UserProjection::new().with_name_matching(NameMatching::CaseInsensitive);
+    register(&projection, 1, "Alice");
+
+    let found = projection.find_by_name("ALICE").expect("Should match ignoring case");
+    assert_eq!((found.id, found.name.as_str()), (1, "Alice"));
+
+    rename(&projection, 1, "aLiCe");
+    assert_eq!(projection.find_by_name("alice").map(|u| u.name), Some("aLiCe".to_string()));
+}
+
+/// Average time of `rounds` lookups spread across `population` users
+fn average_lookup(projection: &UserProjection, population: u32, rounds: u32) -> Duration {
+    let step = (population / rounds).max(1);
+    let start = Instant::now();
+    for i in 0..rounds {
+        let id = (i * step) % population + 1;
+        let found = projection.find_by_name(&format!("User{}", id));
+        assert_eq!(found.map(|u| u.id), Some(id));
+    }
+    start.elapsed() / rounds
+}
+
+/// Timing-based and slow in debug builds; run with
+/// `cargo test --release --test name_index_tests -- --ignored`
+#[test]
+#[ignore = "builds 1M users and asserts on wall-clock time"]
+fn test_lookup_time_is_independent_of_population() {
+    const SMALL: u32 = 1_000;
+    const LARGE: u32 = 1_000_000;
+    const ROUNDS: u32 = 2_000;
+
+    let small = UserProjection::new();
+    for id in 1..=SMALL {
+        register(&small, id, &format!("User{}", id));
+    }
+    let large = UserProjection::new();
+    for id in 1..=LARGE {
+        register(&large, id, &format!("User{}", id));
+    }
+
+    let small_lookup = average_lookup(&small, SMALL, ROUNDS);
+    let large_lookup = average_lookup(&large, LARGE, ROUNDS);
+
+    // A linear scan would be ~1000x slower at the larger size; allow generous
+    // headroom for cache effects and noisy machines.
+    assert!(
+        large_lookup < small_lookup * 20 + Duration::from_micros(20),
+        "Lookups slowed from {:?} to {:?}",
+        small_lookup,
+        large_lookup
+    );
+}
@@ -0,0 +1,181 @@
+//! Projection checkpointing and rebuild from the event log
+//!
+//! The projection records the global position of the last event it applied,
+//! skips anything at or before it, and can be rebuilt by replaying the store.
+//! Rebuilds fill a new version while the old one keeps serving, then swap.
+
+use rust_composition::{
+    commands::MergeUsersCommand,
+    events::{projections::{RebuildStatus, UserProjection, REBUILD_BATCH_SIZE}, UserEvent},
+    simulation::Simulation,
+};
+
+async fn populated() -> Simulation {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    sim.register_user(2, "Bob").await.expect("Register should succeed");
+    sim.rename_user(1, "Alicia").await.expect("Rename should succeed");
+    sim
+}
+
+fn names(projection: &UserProjection) -> Vec<(u32, String)> {
+    projection.get_all_users().into_iter().map(|u| (u.id, u.name)).collect()
+}
+
+#[tokio::test]
+async fn test_live_events_advance_checkpoint() {
+    let sim = populated().await;
+
+    assert_eq!(sim.projection().checkpoint(), Some(2));
+}
+
+#[tokio::test]
+async fn test_replaying_applied_event_is_ignored() {
+    let sim = populated().await;
+    let registered = sim.event_store().read_all_from(0).remove(0);
+
+    assert!(!sim.projection().apply(&registered));
+    assert_eq!(sim.projection().get_user(1).expect("Should find user").name, "Alicia");
+    assert_eq!(sim.projection().checkpoint(), Some(2));
+}
+
+#[tokio::test]
+async fn test_fresh_projection_catches_up_from_log() {
+    let sim = populated().await;
+    let restarted = UserProjection::new();
+
+    assert_eq!(restarted.catch_up(sim.event_store()), 3);
+    assert_eq!(names(&restarted), names(sim.projection()));
+
+    sim.event_store().append(3, UserEvent::Registered {
+        user_id: 3,
+        name: "Carol".to_string(),
+        timestamp: 1000,
+    });
+
+    assert_eq!(restarted.catch_up(sim.event_store()), 1);
+    assert_eq!(restarted.catch_up(sim.event_store()), 0);
+    assert_eq!(restarted.checkpoint(), Some(3));
+}
+
+#[tokio::test]
+async fn test_rebuild_repairs_drifted_projection() {
+    let sim = populated().await;
+    sim.command_handler()
+        .handle_merge_users(MergeUsersCommand::new(2, 1).expect("Valid command"))
+        .await
+        .expect("Merge should succeed");
+    let expected = names(sim.projection());
+
+    // Simulate drift: an event applied outside the log
+    sim.projection().apply_event(&UserEvent::Renamed {
+        user_id: 1,
+        new_name: "Corrupted".to_string(),
+        timestamp: 0,
+    });
+
+    assert_eq!(sim.projection().rebuild(sim.event_store()), Some(5));
+    assert_eq!(names(sim.projection()), expected);
+    assert_eq!(sim.projection().resolve_id(2), 1);
+    assert_eq!(sim.projection().checkpoint(), Some(4));
+}
+
+fn many_users(count: u32) -> Vec<UserEvent> {
+    (1..=count)
+        .map(|id| UserEvent::Registered {
+            user_id: id,
+            name: format!("User{}", id),
+            timestamp: 1000,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_background_rebuild_swaps_in_new_version() {
+    let sim = Simulation::new(1);
+    let total = REBUILD_BATCH_SIZE as u32 * 2 + 500;
+    sim.load_history(many_users(total));
+    let expected = names(sim.projection());
+
+    let handle = sim
+        .projection()
+        .start_rebuild(sim.event_store())
+        .expect("Rebuild should start");
+
+    // The old version keeps serving until the new one is complete
+    let progress = sim.projection().rebuild_progress();
+    assert_eq!(progress.status, RebuildStatus::Building);
+    assert_eq!((progress.serving_version, progress.target_version), (1, Some(2)));
+    assert_eq!(progress.events_total, total as u64);
+    assert_eq!(sim.projection().version(), 1);
+    assert_eq!(sim.projection().get_all_users().len(), total as usize);
+
+    handle.await.expect("Rebuild should finish");
+
+    let progress = sim.projection().rebuild_progress();
+    assert_eq!(progress.status, RebuildStatus::Completed);
+    assert_eq!(progress.events_applied, total as usize);
+    assert_eq!(sim.projection().version(), 2);
+    assert_eq!(names(sim.projection()), expected);
+}
+
+#[tokio::test]
+async fn test_events_during_background_rebuild_reach_new_version() {
+    let sim = Simulation::new(1);
+    sim.load_history(many_users(REBUILD_BATCH_SIZE as u32 * 3));
+
+    let handle = sim
+        .projection()
+        .start_rebuild(sim.event_store())
+        .expect("Rebuild should start");
+    tokio::task::yield_now().await;
+    assert!(sim.projection().start_rebuild(sim.event_store()).is_none());
+
+    sim.rename_user(1, "Renamed mid-rebuild").await.expect("Rename should succeed");
+    handle.await.expect("Rebuild should finish");
+
+    assert_eq!(sim.projection().version(), 2);
+    assert_eq!(sim.projection().get_user(1).expect("Should find user").name, "Renamed mid-rebuild");
+    assert_eq!(sim.projection().checkpoint(), Some(sim.event_store().head_position() - 1));
+
+    sim.rename_user(2, "After swap").await.expect("Rename should succeed");
+    assert_eq!(sim.projection().get_user(2).expect("Should find user").name, "After swap");
+}
+
+#[tokio::test]
+async fn test_aborted_rebuild_is_marked_failed_and_can_be_retried() {
+    let sim = Simulation::new(1);
+    sim.load_history(many_users(REBUILD_BATCH_SIZE as u32 * 2));
+
+    let handle = sim
+        .projection()
+        .start_rebuild(sim.event_store())
+        .expect("Rebuild should start");
+    handle.abort();
+    assert!(handle.await.is_err(), "Task should have been cancelled");
+
+    assert_eq!(sim.projection().rebuild_progress().status, RebuildStatus::Failed);
+    assert_eq!(sim.projection().version(), 1, "The old version keeps serving");
+    let retry = sim
+        .projection()
+        .start_rebuild(sim.event_store())
+        .expect("A failed rebuild must not block the next one");
+    retry.await.expect("Rebuild should finish");
+    assert_eq!(sim.projection().rebuild_progress().status, RebuildStatus::Completed);
+}
+
+#[tokio::test]
+async fn test_synchronous_rebuild_is_refused_during_background_rebuild() {
+    let sim = Simulation::new(1);
+    sim.load_history(many_users(REBUILD_BATCH_SIZE as u32 * 2));
+
+    let handle = sim
+        .projection()
+        .start_rebuild(sim.event_store())
+        .expect("Rebuild should start");
+
+    assert_eq!(sim.projection().rebuild(sim.event_store()), None);
+    handle.await.expect("Rebuild should finish");
+    assert_eq!(sim.projection().version(), 2);
+    assert!(sim.projection().rebuild(sim.event_store()).is_some());
+}
@@ -0,0 +1,216 @@
+//! Projection registry
+//!
+//! Named projections are fed from the event bus through one registry,
+//! report their checkpoint, status and lag, and can be stopped, started
+//! and rebuilt independently of each other.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use rust_composition::{
+    domain::errors::{AppError, DomainResult},
+    events::{
+        event_bus::ReplayError,
+        projections::{
+            ManagedProjection, ProjectionRegistry, ProjectionStatus, SearchMode,
+            UserProjection, UserSearchProjection,
+        },
+        EventEnvelope, EventStore,
+    },
+    simulation::Simulation,
+};
+
+/// Projection that counts what it applies and can be told to fail
+#[derive(Default)]
+struct FlakyProjection {
+    checkpoint: Mutex<Option<u64>>,
+    failing: AtomicBool,
+}
+
+impl ManagedProjection for FlakyProjection {
+    fn name(&self) -> &str {
+        "flaky"
+    }
+
+    fn checkpoint(&self) -> DomainResult<Option<u64>> {
+        Ok(*self.checkpoint.lock().unwrap())
+    }
+
+    fn apply(&self, envelope: &EventEnvelope) -> DomainResult<bool> {
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(AppError::RepositoryError("flaky store unavailable".to_string()));
+        }
+        let mut checkpoint = self.checkpoint.lock().unwrap();
+        if checkpoint.is_some_and(|applied| envelope.global_position <= applied) {
+            return Ok(false);
+        }
+        *checkpoint = Some(envelope.global_position);
+        Ok(true)
+    }
+
+    fn catch_up(&self, event_store: &EventStore) -> DomainResult<usize> {
+        let from = self.checkpoint()?.map_or(0, |position| position + 1);
+        let mut applied = 0;
+        for envelope in event_store.read_all_from(from) {
+            applied += usize::from(self.apply(&envelope)?);
+        }
+        Ok(applied)
+    }
+
+    fn rebuild(&self, event_store: &EventStore) -> DomainResult<usize> {
+        *self.checkpoint.lock().unwrap() = None;
+        self.catch_up(event_store)
+    }
+}
+
+/// Simulation with a registry holding fresh users and search projections
+fn registered(sim: &Simulation) -> (Arc<ProjectionRegistry>, UserProjection, UserSearchProjection) {
+    let registry = Arc::new(ProjectionRegistry::new(sim.event_store().clone()));
+    let users = UserProjection::new();
+    let search = UserSearchProjection::new();
+    registry.register(Arc::new(users.clone())).expect("Should register");
+    registry.register(Arc::new(search.clone())).expect("Should register");
+    sim.event_bus().subscribe(registry.clone());
+    (registry, users, search)
+}
+
+#[tokio::test]
+async fn test_start_catches_up_on_existing_events() {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    let (registry, users, search) = registered(&sim);
+    assert_eq!(registry.info("users").unwrap().status, ProjectionStatus::Stopped);
+    assert_eq!(registry.info("users").unwrap().lag, 1);
+
+    registry.start_all().expect("Should start");
+    sim.register_user(2, "Bob").await.expect("Register should succeed");
+
+    assert_eq!(registry.names(), vec!["user_search", "users"]);
+    for info in registry.list() {
+        assert_eq!(info.status, ProjectionStatus::Running);
+        assert_eq!(info.checkpoint, Some(1));
+        assert_eq!(info.lag, 0);
+    }
+    assert_eq!(users.get_all_users(), sim.projection().get_all_users());
+    assert_eq!(search.search("Bo", SearchMode::Prefix, 10).len(), 1);
+}
+
+#[tokio::test]
+async fn test_stopped_projection_lags_then_catches_up() {
+    let sim = Simulation::new(1);
+    let (registry, users, _) = registered(&sim);
+    registry.start_all().expect("Should start");
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+
+    registry.stop("users").expect("Should stop");
+    sim.rename_user(1, "Alicia").await.expect("Rename should succeed");
+    sim.register_user(2, "Bob").await.expect("Register should succeed");
+
+    let stopped = registry.info("users").unwrap();
+    assert_eq!((stopped.status, stopped.checkpoint, stopped.lag), (ProjectionStatus::Stopped, Some(0), 2));
+    assert_eq!(users.get_user(1).unwrap().name, "Alice", "Stopped projection keeps serving");
+    assert_eq!(registry.info("user_search").unwrap().lag, 0, "Others keep running");
+
+    let started = registry.start("users").expect("Should start");
+    assert_eq!((started.status, started.lag), (ProjectionStatus::Running, 0));
+    assert_eq!(users.get_all_users(), sim.projection().get_all_users());
+}
+
+#[tokio::test]
+async fn test_rebuild_reproduces_projection() {
+    let sim = Simulation::new(1);
+    let (registry, users, _) = registered(&sim);
+    registry.start_all().expect("Should start");
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    sim.rename_user(1, "Alicia").await.expect("Rename should succeed");
+    let version = registry.info("users").unwrap().version;
+
+    let rebuilt = registry.rebuild("users").expect("Should rebuild");
+
+    assert_eq!(rebuilt.status, ProjectionStatus::Running);
+    assert_eq!(rebuilt.version, version + 1);
+    assert_eq!(rebuilt.checkpoint, Some(1));
+    assert_eq!(users.get_all_users(), sim.projection().get_all_users());
+}
+
+#[tokio::test]
+async fn test_failing_projection_is_isolated() {
+    let sim = Simulation::new(1);
+    let (registry, users, _) = registered(&sim);
+    let flaky = Arc::new(FlakyProjection::default());
+    registry.register(flaky.clone()).expect("Should register");
+    registry.start_all().expect("Should start");
+
+    flaky.failing.store(true, Ordering::SeqCst);
+    sim.register_user(1, "Alice").await.expect("Command succeeds despite the failure");
+    sim.register_user(2, "Bob").await.expect("Register should succeed");
+
+    let failed = registry.info("flaky").unwrap();
+    assert_eq!((failed.status, failed.checkpoint, failed.lag), (ProjectionStatus::Failed, None, 2));
+    assert!(failed.last_error.unwrap().contains("flaky store unavailable"));
+    assert_eq!(users.get_all_users().len(), 2, "Other projections keep running");
+
+    assert!(registry.start("flaky").is_err(), "Still failing on restart");
+    flaky.failing.store(false, Ordering::SeqCst);
+    let restarted = registry.start("flaky").expect("Should restart");
+    assert_eq!((restarted.status, restarted.checkpoint, restarted.last_error), (ProjectionStatus::Running, Some(1), None));
+}
+
+#[tokio::test]
+async fn test_failures_are_dead_lettered_and_replayed_per_projection() {
+    let sim = Simulation::new(1);
+    let (registry, _, _) = registered(&sim);
+    let flaky = Arc::new(FlakyProjection::default());
+    registry.register(flaky.clone()).expect("Should register");
+    registry.start_all().expect("Should start");
+
+    flaky.failing.store(true, Ordering::SeqCst);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    let dead_letters = sim.event_store().get_dead_letter_queue();
+    assert_eq!(dead_letters.len(), 1, "Only the failing projection is dead-lettered");
+    let entry = &dead_letters[0];
+    assert_eq!(entry.handler_name.as_deref(), Some("ProjectionRegistry"));
+    assert_eq!(entry.target.as_deref(), Some("flaky"));
+    assert_eq!(entry.error_message, "Repository error: flaky store unavailable");
+
+    let still_failing = sim.event_bus().replay_dead_letter(entry.id).await;
+    assert!(matches!(still_failing, Err(ReplayError::HandlerFailed(_))));
+    assert_eq!(registry.info("flaky").unwrap().status, ProjectionStatus::Failed);
+
+    flaky.failing.store(false, Ordering::SeqCst);
+    sim.event_bus().replay_dead_letter(entry.id).await.expect("Replay should restart the projection");
+    let replayed = registry.info("flaky").unwrap();
+    assert_eq!((replayed.status, replayed.checkpoint), (ProjectionStatus::Running, Some(0)));
+    assert_eq!(sim.event_store().dlq_size(), 0);
+}
+
+#[tokio::test]
+async fn test_replay_into_a_stopped_projection_is_refused() {
+    let sim = Simulation::new(1);
+    let (registry, _, _) = registered(&sim);
+    let flaky = Arc::new(FlakyProjection::default());
+    registry.register(flaky.clone()).expect("Should register");
+    registry.start_all().expect("Should start");
+    flaky.failing.store(true, Ordering::SeqCst);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    let id = sim.event_store().get_dead_letter_queue()[0].id;
+
+    registry.stop("flaky").expect("Should stop");
+    flaky.failing.store(false, Ordering::SeqCst);
+    let result = sim.event_bus().replay_dead_letter(id).await;
+
+    assert!(matches!(result, Err(ReplayError::HandlerFailed(_))));
+    assert!(sim.event_store().get_dlq_entry(id).is_some(), "Entry stays queued");
+    let stopped = registry.info("flaky").unwrap();
+    assert_eq!((stopped.status, stopped.checkpoint), (ProjectionStatus::Stopped, None));
+}
+
+#[test]
+fn test_duplicate_and_unknown_names_are_rejected() {
+    let registry = ProjectionRegistry::new(EventStore::new());
+    registry.register(Arc::new(UserProjection::new())).expect("Should register");
+
+    let duplicate = registry.register(Arc::new(UserProjection::new()));
+    assert!(matches!(duplicate, Err(AppError::Validation(_))));
+    assert!(matches!(registry.start("missing"), Err(AppError::Validation(_))));
+    assert_eq!(registry.names(), vec!["users"]);
+}
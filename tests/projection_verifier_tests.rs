@@ -0,0 +1,131 @@
+//! Projection consistency verifier
+//!
+//! Every stream is replayed through the aggregate and diffed field by field
+//! against the read model; mismatches can optionally be repaired.
+
+use rust_composition::{
+    commands::MergeUsersCommand,
+    events::{projections::{ConsistencyVerifier, Mismatch}, UserEvent},
+    simulation::Simulation,
+};
+
+async fn populated() -> Simulation {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    sim.register_user(2, "Bob").await.expect("Register should succeed");
+    sim.register_user(3, "Bobby").await.expect("Register should succeed");
+    sim.rename_user(1, "Alicia").await.expect("Rename should succeed");
+    sim.command_handler()
+        .handle_merge_users(MergeUsersCommand::new(3, 2).expect("Valid command"))
+        .await
+        .expect("Merge should succeed");
+    sim
+}
+
+fn verifier(sim: &Simulation) -> ConsistencyVerifier {
+    ConsistencyVerifier::new(sim.event_store().clone(), sim.projection().clone())
+}
+
+#[tokio::test]
+async fn test_projection_built_from_commands_is_consistent() {
+    let sim = populated().await;
+
+    let report = verifier(&sim).verify();
+
+    assert_eq!(report.streams_checked, 3);
+    assert!(report.is_consistent(), "Unexpected mismatches: {:?}", report.mismatches);
+}
+
+#[tokio::test]
+async fn test_lost_rename_is_reported_then_repaired() {
+    let sim = populated().await;
+    // Event stored but never delivered to the projection
+    sim.event_store().append(1, UserEvent::Renamed {
+        user_id: 1,
+        new_name: "Ally".to_string(),
+        timestamp: 1000,
+    });
+
+    let report = verifier(&sim).verify();
+    assert_eq!(
+        report.mismatches.first(),
+        Some(&Mismatch::FieldMismatch {
+            user_id: 1,
+            field: "name",
+            expected: "Ally".to_string(),
+            actual: "Alicia".to_string(),
+        })
+    );
+    let fields: Vec<&str> = report
+        .mismatches
+        .iter()
+        .map(|mismatch| match mismatch {
+            Mismatch::FieldMismatch { user_id: 1, field, .. } => *field,
+            other => panic!("Unexpected mismatch: {:?}", other),
+        })
+        .collect();
+    assert_eq!(fields, vec!["name", "version", "updated_at", "rename_count"]);
+    assert!(report.repaired.is_empty());
+    assert_eq!(sim.projection().get_user(1).expect("Should find user").name, "Alicia");
+
+    let repaired = verifier(&sim).verify_and_repair();
+    assert_eq!(repaired.repaired, vec![1]);
+    assert_eq!(sim.projection().get_user(1).expect("Should find user").name, "Ally");
+    assert!(verifier(&sim).verify().is_consistent());
+}
+
+#[tokio::test]
+async fn test_corrupted_name_and_version_are_reported_against_the_aggregate() {
+    let sim = populated().await;
+    // Applied to the projection only: the stream still ends at "Alicia", version 1
+    sim.projection().apply_event(&UserEvent::Renamed {
+        user_id: 1,
+        new_name: "Mallory".to_string(),
+        timestamp: 1000,
+    });
+
+    let report = verifier(&sim).verify();
+
+    assert!(report.mismatches.contains(&Mismatch::FieldMismatch {
+        user_id: 1,
+        field: "name",
+        expected: "Alicia".to_string(),
+        actual: "Mallory".to_string(),
+    }));
+    assert!(report.mismatches.contains(&Mismatch::FieldMismatch {
+        user_id: 1,
+        field: "version",
+        expected: "1".to_string(),
+        actual: "2".to_string(),
+    }));
+    verifier(&sim).verify_and_repair();
+    assert_eq!(sim.projection().get_user(1).expect("Should find user").name, "Alicia");
+}
+
+#[tokio::test]
+async fn test_unknown_user_and_missing_redirect_are_repaired() {
+    let sim = populated().await;
+    sim.projection().apply_event(&UserEvent::Registered {
+        user_id: 99,
+        name: "Ghost".to_string(),
+        timestamp: 1000,
+    });
+    sim.event_store().append(2, UserEvent::MergedInto {
+        user_id: 2,
+        target_id: 1,
+        timestamp: 1000,
+    });
+
+    let report = verifier(&sim).verify_and_repair();
+
+    assert!(report.mismatches.contains(&Mismatch::UnexpectedUser { user_id: 99 }));
+    assert!(report.mismatches.contains(&Mismatch::UnexpectedUser { user_id: 2 }));
+    assert!(report.mismatches.contains(&Mismatch::RedirectMismatch {
+        user_id: 2,
+        expected: Some(1),
+        actual: None,
+    }));
+    assert!(sim.projection().get_user(99).is_none());
+    assert_eq!(sim.projection().resolve_id(3), 1);
+    assert!(verifier(&sim).verify().is_consistent());
+}
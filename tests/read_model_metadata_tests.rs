@@ -0,0 +1,89 @@
+//! Version and audit fields on the user read model
+//!
+//! The projection tracks each user's stream version, last-modified time,
+//! rename count and status, and a rebuild or replay arrives at the same values.
+
+use rust_composition::{
+    commands::MergeUsersCommand,
+    events::{projections::{UserReadModel, UserStatus}, UserEvent},
+    simulation::Simulation,
+};
+
+const START: i64 = Simulation::START_MILLIS;
+
+#[tokio::test]
+async fn test_registration_starts_at_version_zero() {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+
+    let user = sim.projection().get_user(1).expect("Should find user");
+    assert_eq!(user.version, 0);
+    assert_eq!(user.created_at, START);
+    assert_eq!(user.updated_at, START);
+    assert_eq!(user.rename_count, 0);
+    assert_eq!(user.status, UserStatus::Active);
+}
+
+#[tokio::test]
+async fn test_renames_advance_version_and_updated_at() {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    sim.rename_user(1, "Alicia").await.expect("Rename should succeed");
+    sim.rename_user(1, "Ally").await.expect("Rename should succeed");
+
+    let user = sim.projection().get_user(1).expect("Should find user");
+    assert_eq!(user.version, 2);
+    assert_eq!(user.created_at, START);
+    assert_eq!(user.updated_at, START + 2_000);
+    assert_eq!(user.rename_count, 2);
+
+    let aggregate = sim.repository().get_by_id_at_version(1, 2).expect("Should load");
+    assert_eq!(user.version, aggregate.version, "Read model version matches the aggregate");
+}
+
+#[tokio::test]
+async fn test_absorbing_a_merge_bumps_target_version() {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    sim.register_user(2, "Alice J").await.expect("Register should succeed");
+    sim.command_handler()
+        .handle_merge_users(MergeUsersCommand::new(2, 1).expect("Valid command"))
+        .await
+        .expect("Merge should succeed");
+
+    let target = sim.projection().get_user(1).expect("Should find target");
+    assert_eq!(target.version, 1);
+    assert_eq!(target.updated_at, START + 2_000);
+    assert_eq!(target.rename_count, 0);
+}
+
+#[tokio::test]
+async fn test_rebuild_reproduces_the_same_fields() {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    sim.register_user(2, "Bob").await.expect("Register should succeed");
+    sim.rename_user(1, "Alicia").await.expect("Rename should succeed");
+    let before = sim.projection().get_all_users();
+
+    sim.projection().rebuild(sim.event_store()).expect("No rebuild should be running");
+
+    assert_eq!(sim.projection().get_all_users(), before);
+}
+
+#[test]
+fn test_replaying_a_merged_stream_reports_merged_status() {
+    let events = vec![
+        UserEvent::Registered { user_id: 2, name: "Bob".to_string(), timestamp: 1000 },
+        UserEvent::Renamed { user_id: 2, new_name: "Bobby".to_string(), timestamp: 2000 },
+        UserEvent::MergedInto { user_id: 2, target_id: 1, timestamp: 3000 },
+    ];
+
+    let user = UserReadModel::from_history(&events).expect("Stream starts with a registration");
+
+    assert_eq!(user.name, "Bobby");
+    assert_eq!(user.version, 2);
+    assert_eq!(user.updated_at, 3000);
+    assert_eq!(user.rename_count, 1);
+    assert_eq!(user.status, UserStatus::Merged);
+    assert!(UserReadModel::from_history(&events[1..]).is_none());
+}
@@ -0,0 +1,67 @@
+//! Deterministic simulation tests
+//!
+//! Each scenario runs against a fully wired stack with a virtual clock and
+//! seeded correlation IDs, so repeated runs must produce identical output.
+
+use rust_composition::simulation::Simulation;
+
+async fn run_scenario(seed: u64) -> Simulation {
+    let sim = Simulation::new(seed);
+
+    for i in 1..=5 {
+        sim.register_user(i, &format!("User{}", i)).await.expect("Register should succeed");
+    }
+    sim.rename_user(3, "Charlie").await.expect("Rename should succeed");
+    sim.advance_millis(3_600_000);
+    sim.rename_user(1, "Alice").await.expect("Rename should succeed");
+    let _ = sim.register_user(6, "Alice").await;
+
+    sim
+}
+
+#[tokio::test]
+async fn test_same_seed_produces_byte_identical_event_logs() {
+    let first = run_scenario(42).await;
+    let second = run_scenario(42).await;
+
+    assert_eq!(first.event_log(), second.event_log());
+    assert_eq!(first.log_messages(), second.log_messages());
+}
+
+#[tokio::test]
+async fn test_different_seeds_only_change_correlation_ids() {
+    let first = run_scenario(1).await;
+    let second = run_scenario(2).await;
+
+    assert_eq!(first.event_log(), second.event_log());
+    assert_ne!(first.log_messages(), second.log_messages());
+}
+
+#[tokio::test]
+async fn test_virtual_clock_drives_event_timestamps() {
+    let sim = Simulation::new(7).with_tick_millis(500);
+
+    sim.register_user(2, "Bob").await.expect("Register should succeed");
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+
+    assert_eq!(
+        sim.event_log(),
+        format!(
+            "UserRegistered(id=1, name=Alice, timestamp={})\nUserRegistered(id=2, name=Bob, timestamp={})\n",
+            Simulation::START_MILLIS + 500,
+            Simulation::START_MILLIS
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_projection_listing_is_ordered_by_id() {
+    let sim = Simulation::new(7);
+
+    for id in [5, 3, 9, 1] {
+        sim.register_user(id, &format!("User{}", id)).await.expect("Register should succeed");
+    }
+
+    let ids: Vec<u32> = sim.projection().get_all_users().iter().map(|u| u.id).collect();
+    assert_eq!(ids, vec![1, 3, 5, 9]);
+}
@@ -0,0 +1,194 @@
+//! SQLite-backed user read model
+//!
+//! SqliteUserProjection answers the same queries as the in-memory
+//! projection, keeps its checkpoint in the same transaction as each batch,
+//! and picks up where it left off when reopened against the same log.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use rust_composition::{
+    commands::MergeUsersCommand,
+    events::{
+        projections::{
+            NameMatching, ProjectionRegistry, SqliteProjectionEventHandler, SqliteUserProjection,
+            UserListQuery, UserQueries, UserSortKey,
+        },
+        EventStore, UserEvent,
+    },
+    simulation::Simulation,
+};
+
+/// Simulation whose events also flow into a SQLite read model
+async fn populated(sqlite: &SqliteUserProjection) -> Simulation {
+    let sim = Simulation::new(1);
+    sim.event_bus()
+        .subscribe(Arc::new(SqliteProjectionEventHandler::new(sqlite.clone())));
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    sim.register_user(2, "Bob").await.expect("Register should succeed");
+    sim.register_user(3, "Bobby").await.expect("Register should succeed");
+    sim.rename_user(1, "Alicia").await.expect("Rename should succeed");
+    sim.command_handler()
+        .handle_merge_users(MergeUsersCommand::new(3, 2).expect("Valid command"))
+        .await
+        .expect("Merge should succeed");
+    sim
+}
+
+fn database_path(test: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("{}-{}.db", test, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[tokio::test]
+async fn test_matches_in_memory_projection() {
+    let sqlite = SqliteUserProjection::open_in_memory().expect("Should open");
+    let sim = populated(&sqlite).await;
+
+    assert_eq!(sqlite.get_all_users().unwrap(), sim.projection().get_all_users());
+    assert_eq!(sqlite.checkpoint().unwrap(), sim.projection().checkpoint());
+    assert_eq!(sqlite.find_by_name("Alicia").unwrap().map(|u| u.id), Some(1));
+    assert!(sqlite.find_by_name("Alice").unwrap().is_none());
+    assert!(sqlite.find_by_name("Bobby").unwrap().is_none(), "Merged name is released");
+    assert_eq!(sqlite.resolve_id(3).unwrap(), 2);
+    assert_eq!(sqlite.get_user(3).unwrap(), sim.projection().get_user(2));
+    assert_eq!(sqlite.get_user(1).unwrap().unwrap().rename_count, 1);
+}
+
+#[tokio::test]
+async fn test_listing_through_shared_query_surface() {
+    let sqlite = SqliteUserProjection::open_in_memory().expect("Should open");
+    let sim = populated(&sqlite).await;
+    let query = UserListQuery {
+        sort: UserSortKey::Name,
+        limit: 1,
+        ..UserListQuery::default()
+    };
+
+    let stores: [&dyn UserQueries; 2] = [&sqlite, sim.projection()];
+    let mut pages = Vec::new();
+    for store in stores {
+        pages.push(store.list_users(&query).await.expect("Should list").users);
+    }
+
+    assert_eq!(pages[0], pages[1]);
+    assert_eq!(pages[0][0].name, "Alicia");
+}
+
+#[tokio::test]
+async fn test_reopened_database_resumes_from_checkpoint() {
+    let path = database_path("sqlite-resume");
+    let store = EventStore::new();
+    store.append(1, UserEvent::Registered { user_id: 1, name: "Alice".to_string(), timestamp: 1000 });
+    store.append(2, UserEvent::Registered { user_id: 2, name: "Bob".to_string(), timestamp: 2000 });
+
+    {
+        let sqlite = SqliteUserProjection::open(&path).expect("Should open");
+        assert_eq!(sqlite.catch_up(&store).unwrap(), 2);
+    }
+
+    store.append(1, UserEvent::Renamed { user_id: 1, new_name: "Alicia".to_string(), timestamp: 3000 });
+    let reopened = SqliteUserProjection::open(&path).expect("Should reopen");
+    assert_eq!(reopened.checkpoint().unwrap(), Some(1));
+    assert_eq!(reopened.get_all_users().unwrap().len(), 2, "Rows survive reopening");
+
+    assert_eq!(reopened.catch_up(&store).unwrap(), 1, "Only the new event is applied");
+    let alice = reopened.get_user(1).unwrap().expect("Should find user");
+    assert_eq!((alice.name.as_str(), alice.version, alice.updated_at), ("Alicia", 1, 3000));
+
+    drop(reopened);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn test_replayed_envelopes_are_skipped() {
+    let sqlite = SqliteUserProjection::open_in_memory().expect("Should open");
+    let sim = populated(&sqlite).await;
+    let before = sqlite.get_all_users().unwrap();
+
+    let replayed = sqlite.apply_batch(&sim.event_store().read_all_from(0)).unwrap();
+
+    assert_eq!(replayed, 0);
+    assert_eq!(sqlite.get_all_users().unwrap(), before);
+}
+
+#[tokio::test]
+async fn test_case_insensitive_matching_rekeys_stored_rows() {
+    let path = database_path("sqlite-rekey");
+    let store = EventStore::new();
+    store.append(1, UserEvent::Registered { user_id: 1, name: "Alice".to_string(), timestamp: 1000 });
+    SqliteUserProjection::open(&path).unwrap().catch_up(&store).unwrap();
+
+    let reopened = SqliteUserProjection::open(&path)
+        .and_then(|sqlite| sqlite.with_name_matching(NameMatching::CaseInsensitive))
+        .expect("Should reopen");
+
+    assert_eq!(reopened.find_by_name("ALICE").unwrap().map(|u| u.id), Some(1));
+    drop(reopened);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn test_attach_to_another_log_clears_the_database() {
+    let sqlite = SqliteUserProjection::open_in_memory().expect("Should open");
+    let sim = populated(&sqlite).await;
+    assert!(sqlite.attach(sim.event_store()).unwrap(), "Rows of unknown origin are cleared");
+    sqlite.catch_up(sim.event_store()).unwrap();
+    assert!(!sqlite.attach(sim.event_store()).unwrap(), "Same log keeps its rows");
+
+    // A log with more events than the database has applied is still another log
+    let other_log = EventStore::new();
+    for id in 1..=10 {
+        other_log.append(id, UserEvent::Registered { user_id: id, name: format!("User{}", id), timestamp: 1000 });
+    }
+    assert!(sqlite.attach(&other_log).unwrap());
+
+    assert_eq!(sqlite.checkpoint().unwrap(), None);
+    assert!(sqlite.get_all_users().unwrap().is_empty());
+    assert_eq!(sqlite.resolve_id(3).unwrap(), 3);
+    assert_eq!(sqlite.catch_up(&other_log).unwrap(), 10);
+}
+
+#[tokio::test]
+async fn test_listing_pages_match_in_memory_projection() {
+    let sqlite = SqliteUserProjection::open_in_memory().expect("Should open");
+    let sim = populated(&sqlite).await;
+    for sort in [UserSortKey::Id, UserSortKey::Name, UserSortKey::CreatedAt] {
+        for descending in [false, true] {
+            let mut query = UserListQuery { sort, descending, limit: 1, ..UserListQuery::default() };
+            loop {
+                let expected = sim.projection().list_users(&query).unwrap();
+                let page = sqlite.list_users(&query).unwrap();
+                assert_eq!(page.users, expected.users, "{:?} descending={}", sort, descending);
+                assert_eq!(page.next_cursor, expected.next_cursor);
+                match page.next_cursor {
+                    Some(cursor) => query.after = Some(cursor),
+                    None => break,
+                }
+            }
+        }
+    }
+
+    let filtered = UserListQuery {
+        name_prefix: Some("Ali".to_string()),
+        created_to: Some(i64::MAX),
+        ..UserListQuery::default()
+    };
+    assert_eq!(sqlite.list_users(&filtered).unwrap().users, sim.projection().list_users(&filtered).unwrap().users);
+}
+
+#[tokio::test]
+async fn test_registry_feeds_the_database_from_the_bus() {
+    let sim = Simulation::new(1);
+    let sqlite = SqliteUserProjection::open_in_memory().expect("Should open");
+    let registry = Arc::new(ProjectionRegistry::new(sim.event_store().clone()));
+    registry.register(Arc::new(sqlite.clone())).expect("Should register");
+    registry.start_all().expect("Should start");
+    sim.event_bus().subscribe(registry.clone());
+
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    sim.rename_user(1, "Alicia").await.expect("Rename should succeed");
+
+    assert_eq!(sqlite.get_all_users().unwrap(), sim.projection().get_all_users());
+    assert_eq!(registry.info("users_sqlite").unwrap().lag, 0);
+}
@@ -0,0 +1,109 @@
+//! Store-level catch-up and live subscriptions
+//!
+//! A subscription replays history from a global position and then switches
+//! to live appends without gaps or duplicates.
+
+use std::time::Duration;
+use rust_composition::{
+    events::{EventStore, UserEvent},
+    simulation::Simulation,
+};
+use tokio_stream::StreamExt;
+
+fn registered(user_id: u32) -> UserEvent {
+    UserEvent::Registered {
+        user_id,
+        name: format!("User{}", user_id),
+        timestamp: 1000,
+    }
+}
+
+#[tokio::test]
+async fn test_subscription_replays_history_then_follows_live_appends() {
+    let store = EventStore::new();
+    for id in 1..=3 {
+        store.append(id, registered(id));
+    }
+
+    let mut subscription = store.subscribe_from(1);
+    store.append(4, registered(4));
+    store.append(5, registered(5));
+
+    let mut positions = Vec::new();
+    for _ in 0..4 {
+        let envelope = tokio::time::timeout(Duration::from_secs(1), subscription.next())
+            .await
+            .expect("Event should arrive")
+            .expect("Stream should stay open");
+        positions.push(envelope.global_position);
+    }
+
+    assert_eq!(positions, vec![1, 2, 3, 4]);
+}
+
+#[tokio::test]
+async fn test_subscription_beyond_the_head_skips_earlier_live_appends() {
+    let store = EventStore::new();
+    store.append(1, registered(1));
+
+    let mut subscription = store.subscribe_from(3);
+    for id in 2..=4 {
+        store.append(id, registered(id));
+    }
+
+    let envelope = tokio::time::timeout(Duration::from_secs(1), subscription.next())
+        .await
+        .expect("Event should arrive")
+        .expect("Stream should stay open");
+    assert_eq!(envelope.global_position, 3);
+}
+
+#[tokio::test]
+async fn test_subscription_has_no_gaps_or_duplicates_under_concurrent_appends() {
+    const TOTAL: u32 = 500;
+    let store = EventStore::new();
+
+    let writer = {
+        let store = store.clone();
+        tokio::spawn(async move {
+            for id in 1..=TOTAL {
+                store.append(id, registered(id));
+                if id % 50 == 0 {
+                    tokio::task::yield_now().await;
+                }
+            }
+        })
+    };
+
+    tokio::task::yield_now().await;
+    let subscription = store.subscribe_from(0);
+    writer.await.expect("Writer should finish");
+
+    let positions: Vec<u64> = subscription
+        .take(TOTAL as usize)
+        .map(|envelope| envelope.global_position)
+        .collect()
+        .await;
+
+    assert_eq!(positions, (0..TOTAL as u64).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn test_late_subscriber_sees_events_written_through_command_handler() {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    sim.rename_user(1, "Alicia").await.expect("Rename should succeed");
+
+    let mut subscription = sim.event_store().subscribe_from(0);
+    sim.register_user(2, "Bob").await.expect("Register should succeed");
+
+    let mut events = Vec::new();
+    for _ in 0..3 {
+        events.push(subscription.next().await.expect("Stream should stay open").event);
+    }
+
+    assert!(matches!(events[0], UserEvent::Registered { user_id: 1, .. }));
+    assert!(matches!(events[1], UserEvent::Renamed { user_id: 1, .. }));
+    assert!(matches!(events[2], UserEvent::Registered { user_id: 2, .. }));
+    assert_eq!(sim.event_store().head_position(), 3);
+}
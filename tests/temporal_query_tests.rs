@@ -0,0 +1,56 @@
+//! Temporal queries
+//!
+//! Users can be replayed as they were at a past instant or stream version.
+
+use rust_composition::{
+    infrastructure::DomainError,
+    simulation::Simulation,
+};
+
+const HOUR: i64 = 3_600_000;
+
+async fn renamed_twice() -> Simulation {
+    let sim = Simulation::new(1).with_tick_millis(HOUR);
+    sim.register_user(7, "Alice").await.expect("Register should succeed");
+    sim.rename_user(7, "Alicia").await.expect("Rename should succeed");
+    sim.rename_user(7, "Ally").await.expect("Rename should succeed");
+    sim
+}
+
+#[tokio::test]
+async fn test_get_by_id_as_of_replays_events_up_to_instant() {
+    let sim = renamed_twice().await;
+    let repository = sim.repository();
+    let start = Simulation::START_MILLIS;
+
+    assert_eq!(repository.get_by_id_as_of(7, start).expect("Registered").name, "Alice");
+    assert_eq!(repository.get_by_id_as_of(7, start + HOUR + 1).expect("Renamed").name, "Alicia");
+
+    let latest = repository.get_by_id_as_of(7, start + 10 * HOUR).expect("Renamed twice");
+    assert_eq!(latest.name, "Ally");
+    assert_eq!(latest.version, 2);
+}
+
+#[tokio::test]
+async fn test_get_by_id_as_of_before_registration_is_not_found() {
+    let sim = renamed_twice().await;
+
+    let result = sim.repository().get_by_id_as_of(7, Simulation::START_MILLIS - 1);
+
+    assert_eq!(result.err(), Some(DomainError::AggregateNotFound(7)));
+}
+
+#[tokio::test]
+async fn test_get_by_id_at_version_replays_prefix_of_stream() {
+    let sim = renamed_twice().await;
+    let repository = sim.repository();
+
+    let registered = repository.get_by_id_at_version(7, 0).expect("Version 0");
+    assert_eq!((registered.name.as_str(), registered.version), ("Alice", 0));
+
+    let renamed = repository.get_by_id_at_version(7, 1).expect("Version 1");
+    assert_eq!((renamed.name.as_str(), renamed.version), ("Alicia", 1));
+
+    let result = repository.get_by_id_at_version(7, 3);
+    assert!(matches!(result, Err(DomainError::Validation(msg)) if msg.contains("current version is 2")));
+}
@@ -0,0 +1,103 @@
+//! Compensating "undo last change" command
+//!
+//! Undo never rewrites history: it appends a compensating event whose
+//! causation ID points at the event being reverted.
+
+use rust_composition::{
+    commands::UndoLastChangeCommand,
+    events::UserEvent,
+    infrastructure::DomainError,
+    simulation::Simulation,
+};
+
+#[tokio::test]
+async fn test_undo_reverts_rename_with_causation_link() {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    sim.rename_user(1, "Alcie").await.expect("Rename should succeed");
+
+    let cmd = UndoLastChangeCommand::new(1)
+        .expect("Valid command")
+        .with_reason("Typo".to_string())
+        .with_actor("support:jdoe".to_string());
+    let compensating = sim
+        .command_handler()
+        .handle_undo_last_change(cmd)
+        .await
+        .expect("Undo should succeed");
+
+    assert_eq!(compensating.event_id(), "User-1-2");
+    assert_eq!(compensating.causation_id, Some("User-1-1".to_string()));
+    assert_eq!(compensating.annotations.get("reason"), Some(&"Typo".to_string()));
+    assert_eq!(compensating.actor, Some("support:jdoe".to_string()));
+    assert!(matches!(
+        &compensating.event,
+        UserEvent::Renamed { new_name, .. } if new_name == "Alice"
+    ));
+
+    // History is appended to, never rewritten
+    assert_eq!(sim.event_store().get_events(1).len(), 3);
+    assert_eq!(sim.projection().get_user(1).expect("Should find user").name, "Alice");
+}
+
+#[tokio::test]
+async fn test_undo_after_registration_only_is_rejected() {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+
+    let result = sim
+        .command_handler()
+        .handle_undo_last_change(UndoLastChangeCommand::new(1).expect("Valid command"))
+        .await;
+
+    assert!(matches!(result, Err(DomainError::Validation(msg)) if msg.contains("Nothing to undo")));
+    assert_eq!(sim.event_store().get_events(1).len(), 1);
+}
+
+#[tokio::test]
+async fn test_undo_rechecks_uniqueness_of_restored_name() {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    sim.rename_user(1, "Alicia").await.expect("Rename should succeed");
+    sim.register_user(2, "Alice").await.expect("Name was released by the rename");
+
+    let result = sim
+        .command_handler()
+        .handle_undo_last_change(UndoLastChangeCommand::new(1).expect("Valid command"))
+        .await;
+
+    assert!(matches!(result, Err(DomainError::Validation(msg)) if msg.contains("taken by user ID 2")));
+    assert_eq!(sim.projection().get_user(1).expect("Should find user").name, "Alicia");
+}
+
+#[tokio::test]
+async fn test_undo_unknown_user_is_not_found() {
+    let sim = Simulation::new(1);
+
+    let result = sim
+        .command_handler()
+        .handle_undo_last_change(UndoLastChangeCommand::new(9).expect("Valid command"))
+        .await;
+
+    assert_eq!(result.unwrap_err(), DomainError::AggregateNotFound(9));
+}
+
+#[tokio::test]
+async fn test_undoing_an_undo_reapplies_the_change() {
+    let sim = Simulation::new(1);
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    sim.rename_user(1, "Alicia").await.expect("Rename should succeed");
+    let handler = sim.command_handler();
+
+    handler
+        .handle_undo_last_change(UndoLastChangeCommand::new(1).expect("Valid command"))
+        .await
+        .expect("First undo should succeed");
+    let redo = handler
+        .handle_undo_last_change(UndoLastChangeCommand::new(1).expect("Valid command"))
+        .await
+        .expect("Second undo should succeed");
+
+    assert_eq!(redo.causation_id, Some("User-1-2".to_string()));
+    assert_eq!(sim.projection().get_user(1).expect("Should find user").name, "Alicia");
+}
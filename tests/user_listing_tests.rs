@@ -0,0 +1,139 @@
+//! Cursor-paginated, sorted and filtered user listing
+//!
+//! Pages are ordered by a stable sort key with ties broken by ID, so walking
+//! a listing with `next_cursor` never repeats or skips a user.
+
+use rust_composition::{
+    events::{
+        projections::{UserCursor, UserListQuery, UserProjection, UserSortKey},
+        UserEvent,
+    },
+    infrastructure::DomainError,
+};
+
+fn projection_with(users: &[(u32, &str, i64)]) -> UserProjection {
+    let projection = UserProjection::new();
+    for &(user_id, name, timestamp) in users {
+        projection.apply_event(&UserEvent::Registered {
+            user_id,
+            name: name.to_string(),
+            timestamp,
+        });
+    }
+    projection
+}
+
+/// Follow next_cursor to the end, returning the IDs of each page
+fn walk(projection: &UserProjection, mut query: UserListQuery) -> Vec<Vec<u32>> {
+    let mut pages = Vec::new();
+    loop {
+        let page = projection.list_users(&query).expect("Query should succeed");
+        pages.push(page.users.iter().map(|u| u.id).collect());
+        match page.next_cursor {
+            Some(cursor) => query.after = Some(cursor),
+            None => return pages,
+        }
+    }
+}
+
+#[test]
+fn test_pages_by_id_until_cursor_runs_out() {
+    let projection = projection_with(&[
+        (5, "Eve", 50), (2, "Bob", 20), (7, "Grace", 70), (1, "Alice", 10),
+        (4, "Dan", 40), (3, "Carol", 30), (6, "Frank", 60),
+    ]);
+
+    let pages = walk(&projection, UserListQuery { limit: 3, ..Default::default() });
+
+    assert_eq!(pages, vec![vec![1, 2, 3], vec![4, 5, 6], vec![7]]);
+}
+
+#[test]
+fn test_sort_by_created_at_breaks_ties_by_id() {
+    let projection = projection_with(&[
+        (4, "Dan", 100), (1, "Alice", 200), (3, "Carol", 100), (2, "Bob", 100),
+    ]);
+
+    let ascending = walk(&projection, UserListQuery {
+        sort: UserSortKey::CreatedAt,
+        limit: 2,
+        ..Default::default()
+    });
+    let descending = walk(&projection, UserListQuery {
+        sort: UserSortKey::CreatedAt,
+        descending: true,
+        limit: 2,
+        ..Default::default()
+    });
+
+    assert_eq!(ascending, vec![vec![2, 3], vec![4, 1]]);
+    assert_eq!(descending, vec![vec![1, 4], vec![3, 2]]);
+}
+
+#[test]
+fn test_name_prefix_and_created_range_filters() {
+    let projection = projection_with(&[
+        (1, "Alice", 10), (2, "Alicia", 20), (3, "Bob", 30), (4, "Alistair", 40),
+    ]);
+
+    let page = projection
+        .list_users(&UserListQuery {
+            sort: UserSortKey::Name,
+            name_prefix: Some("Ali".to_string()),
+            created_from: Some(20),
+            created_to: Some(50),
+            ..Default::default()
+        })
+        .expect("Query should succeed");
+
+    let names: Vec<&str> = page.users.iter().map(|u| u.name.as_str()).collect();
+    assert_eq!(names, vec!["Alicia", "Alistair"]);
+    assert!(page.next_cursor.is_none());
+}
+
+#[test]
+fn test_cursor_survives_round_trip_and_inserts_before_it() {
+    let projection = projection_with(&[(10, "Jay", 1), (20, "Kim", 2), (30, "Lee", 3)]);
+    let first = projection
+        .list_users(&UserListQuery { sort: UserSortKey::Name, limit: 2, ..Default::default() })
+        .expect("Query should succeed");
+    let encoded = first.next_cursor.expect("More users remain").encode();
+
+    // A user sorting before the cursor must not shift the next page
+    projection.apply_event(&UserEvent::Registered {
+        user_id: 5,
+        name: "Abe".to_string(),
+        timestamp: 4,
+    });
+
+    let second = projection
+        .list_users(&UserListQuery {
+            sort: UserSortKey::Name,
+            after: Some(UserCursor::decode(&encoded).expect("Cursor should decode")),
+            limit: 2,
+            ..Default::default()
+        })
+        .expect("Query should succeed");
+
+    assert_eq!(second.users.iter().map(|u| u.id).collect::<Vec<_>>(), vec![30]);
+}
+
+#[test]
+fn test_mismatched_or_garbage_cursor_is_rejected() {
+    let projection = projection_with(&[(1, "Alice", 10), (2, "Bob", 20)]);
+    let cursor = projection
+        .list_users(&UserListQuery { limit: 1, ..Default::default() })
+        .expect("Query should succeed")
+        .next_cursor
+        .expect("More users remain");
+
+    let result = projection.list_users(&UserListQuery {
+        sort: UserSortKey::Name,
+        after: Some(cursor),
+        ..Default::default()
+    });
+
+    assert!(matches!(result, Err(DomainError::Validation(msg)) if msg.contains("different sort")));
+    assert!(UserCursor::decode("not-a-cursor").is_err());
+    assert!(UserCursor::decode("7a7a").is_err());
+}
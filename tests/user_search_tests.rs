@@ -0,0 +1,85 @@
+//! Type-ahead user search
+//!
+//! UserSearchProjection is fed by the event bus and answers prefix,
+//! substring and typo-tolerant queries with ranked results.
+
+use std::sync::Arc;
+use rust_composition::{
+    commands::MergeUsersCommand,
+    events::projections::{
+        bounded_edit_distance, SearchMode, SearchProjectionEventHandler, UserSearchProjection,
+    },
+    simulation::Simulation,
+};
+
+async fn searchable(names: &[&str]) -> (Simulation, UserSearchProjection) {
+    let sim = Simulation::new(1);
+    let search = UserSearchProjection::new();
+    sim.event_bus()
+        .subscribe(Arc::new(SearchProjectionEventHandler::new(search.clone())));
+    for (i, name) in names.iter().enumerate() {
+        sim.register_user(i as u32 + 1, name).await.expect("Register should succeed");
+    }
+    (sim, search)
+}
+
+fn names(search: &UserSearchProjection, query: &str, mode: SearchMode) -> Vec<String> {
+    search.search(query, mode, 10).into_iter().map(|hit| hit.name).collect()
+}
+
+#[tokio::test]
+async fn test_prefix_ranks_shorter_names_first() {
+    let (_sim, search) = searchable(&["Alexandra", "Alex", "Bob", "Alexa"]).await;
+
+    assert_eq!(names(&search, "ale", SearchMode::Prefix), vec!["Alex", "Alexa", "Alexandra"]);
+    assert!(names(&search, "lex", SearchMode::Prefix).is_empty());
+}
+
+#[tokio::test]
+async fn test_substring_matches_anywhere_ignoring_case() {
+    let (_sim, search) = searchable(&["Alice Smith", "Bob SMITHERS", "Carol"]).await;
+
+    assert_eq!(names(&search, "SMITH", SearchMode::Substring), vec!["Alice Smith", "Bob SMITHERS"]);
+}
+
+#[tokio::test]
+async fn test_fuzzy_tolerates_typos_and_ranks_by_distance() {
+    let (_sim, search) = searchable(&["Alice", "Alicia", "Bob"]).await;
+
+    let hits = search.search("alcie", SearchMode::Fuzzy, 10);
+    assert_eq!(hits.first().map(|h| h.name.as_str()), Some("Alice"));
+    assert!(hits.windows(2).all(|w| w[0].score <= w[1].score));
+    assert!(names(&search, "bob", SearchMode::Fuzzy).contains(&"Bob".to_string()));
+    assert!(names(&search, "bb", SearchMode::Fuzzy).is_empty(), "Short queries must match exactly");
+}
+
+#[tokio::test]
+async fn test_renames_and_merges_update_the_index() {
+    let (sim, search) = searchable(&["Alice", "Alicia"]).await;
+
+    sim.rename_user(1, "Zoe").await.expect("Rename should succeed");
+    assert_eq!(names(&search, "ali", SearchMode::Prefix), vec!["Alicia"]);
+    assert_eq!(names(&search, "zoe", SearchMode::Prefix), vec!["Zoe"]);
+
+    sim.command_handler()
+        .handle_merge_users(MergeUsersCommand::new(2, 1).expect("Valid command"))
+        .await
+        .expect("Merge should succeed");
+    assert!(names(&search, "ali", SearchMode::Prefix).is_empty());
+}
+
+#[tokio::test]
+async fn test_limit_truncates_ranked_results() {
+    let (_sim, search) = searchable(&["Ann", "Anna", "Annabel", "Anne"]).await;
+
+    let hits = search.search("ann", SearchMode::Prefix, 2);
+    assert_eq!(hits.iter().map(|h| h.user_id).collect::<Vec<_>>(), vec![1, 2]);
+    assert!(search.search("   ", SearchMode::Prefix, 10).is_empty());
+}
+
+#[test]
+fn test_bounded_edit_distance_gives_up_past_max() {
+    assert_eq!(bounded_edit_distance("kitten", "sitting", 3), Some(3));
+    assert_eq!(bounded_edit_distance("kitten", "sitting", 2), None);
+    assert_eq!(bounded_edit_distance("same", "same", 0), Some(0));
+}
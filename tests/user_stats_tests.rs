@@ -0,0 +1,105 @@
+//! User statistics projection
+//!
+//! UserStatsProjection counts signups, renames and merges per time bucket,
+//! tracks active versus deactivated users and ranks the most renamed ones,
+//! and can be rebuilt from the event log.
+
+use std::sync::Arc;
+use rust_composition::{
+    commands::MergeUsersCommand,
+    domain::errors::AppError,
+    events::projections::{ProjectionRegistry, StatsBucket, UserStatsProjection},
+    simulation::Simulation,
+};
+
+const START: i64 = Simulation::START_MILLIS;
+const DAY: i64 = 24 * 3_600_000;
+
+/// Simulation whose events also flow into a registered stats projection
+fn with_stats() -> (Simulation, Arc<ProjectionRegistry>, UserStatsProjection) {
+    let sim = Simulation::new(1);
+    let registry = Arc::new(ProjectionRegistry::new(sim.event_store().clone()));
+    let stats = UserStatsProjection::new();
+    registry.register(Arc::new(stats.clone())).expect("Should register");
+    registry.start_all().expect("Should start");
+    sim.event_bus().subscribe(registry.clone());
+    (sim, registry, stats)
+}
+
+/// Two signups on the first day; a signup and two renames the next
+async fn two_days(sim: &Simulation) {
+    sim.register_user(1, "Alice").await.expect("Register should succeed");
+    sim.register_user(2, "Bob").await.expect("Register should succeed");
+    sim.advance_millis(DAY);
+    sim.register_user(3, "Carol").await.expect("Register should succeed");
+    sim.rename_user(1, "Alicia").await.expect("Rename should succeed");
+    sim.rename_user(1, "Ally").await.expect("Rename should succeed");
+}
+
+#[tokio::test]
+async fn test_counts_per_day() {
+    let (sim, _, stats) = with_stats();
+    two_days(&sim).await;
+
+    let daily = stats.stats(StatsBucket::Day, 10);
+    let counts: Vec<_> = daily.buckets.iter().map(|b| (b.start, b.signups, b.renames)).collect();
+
+    let first_day = StatsBucket::Day.start_of(START);
+    let second_day = StatsBucket::Day.start_of(START + DAY + 2_000);
+    assert_eq!(counts, vec![(first_day, 2, 0), (second_day, 1, 2)]);
+}
+
+#[tokio::test]
+async fn test_bucket_size_rolls_up_the_same_counters() {
+    let (sim, _, stats) = with_stats();
+    two_days(&sim).await;
+
+    let hourly = stats.stats(StatsBucket::Hour, 10).buckets;
+    let weekly = stats.stats(StatsBucket::Week, 10).buckets;
+
+    assert!(hourly.len() >= 2);
+    assert_eq!(hourly.iter().map(|b| b.signups).sum::<u64>(), 3);
+    assert!(weekly.len() <= 2, "Two consecutive days span at most two weeks");
+    assert_eq!(weekly.iter().map(|b| b.renames).sum::<u64>(), 2);
+    assert!(weekly.iter().all(|b| b.start % StatsBucket::Week.millis() == 0));
+}
+
+#[tokio::test]
+async fn test_merge_deactivates_and_drops_from_rankings() {
+    let (sim, _, stats) = with_stats();
+    two_days(&sim).await;
+    sim.rename_user(2, "Bobby").await.expect("Rename should succeed");
+    sim.rename_user(3, "Caroline").await.expect("Rename should succeed");
+    sim.command_handler()
+        .handle_merge_users(MergeUsersCommand::new(2, 3).expect("Valid command"))
+        .await
+        .expect("Merge should succeed");
+
+    let snapshot = stats.stats(StatsBucket::Day, 10);
+
+    assert_eq!((snapshot.active, snapshot.deactivated), (2, 1));
+    assert_eq!(snapshot.buckets.iter().map(|b| b.merges).sum::<u64>(), 1);
+    let ranked: Vec<_> = snapshot.most_renamed.iter().map(|u| (u.user_id, u.name.as_str(), u.rename_count)).collect();
+    assert_eq!(ranked, vec![(1, "Ally", 2), (3, "Caroline", 1)]);
+    assert_eq!(stats.stats(StatsBucket::Day, 1).most_renamed.len(), 1);
+}
+
+#[tokio::test]
+async fn test_rebuild_from_event_log() {
+    let (sim, registry, stats) = with_stats();
+    two_days(&sim).await;
+    let before = stats.stats(StatsBucket::Hour, 10);
+
+    registry.rebuild("user_stats").expect("Should rebuild");
+    assert_eq!(stats.stats(StatsBucket::Hour, 10), before);
+
+    let fresh = UserStatsProjection::new();
+    assert_eq!(fresh.rebuild(sim.event_store()), 5);
+    assert_eq!(fresh.stats(StatsBucket::Hour, 10), before);
+}
+
+#[test]
+fn test_unknown_bucket_is_rejected() {
+    assert_eq!("week".parse::<StatsBucket>().unwrap(), StatsBucket::Week);
+    assert!(matches!("month".parse::<StatsBucket>(), Err(AppError::Validation(_))));
+}